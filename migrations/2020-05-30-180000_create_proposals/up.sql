@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE proposals (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_name VARCHAR(255) NOT NULL,
+  event_desc VARCHAR(255) NOT NULL,
+  event_loc VARCHAR(255) NOT NULL,
+  organizer VARCHAR(255) NOT NULL,
+  event_time DATETIME NOT NULL,
+  thumbnail_link VARCHAR(255) NOT NULL,
+  proposer_id VARCHAR(255) NOT NULL,
+  message_id VARCHAR(255) NOT NULL
+)
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN creator_id VARCHAR(255) NOT NULL DEFAULT ''
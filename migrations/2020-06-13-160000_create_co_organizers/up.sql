@@ -0,0 +1,6 @@
+-- Your SQL goes here
+CREATE TABLE co_organizers (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_id INTEGER NOT NULL,
+  user_id VARCHAR(255) NOT NULL
+)
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN interested_emoji VARCHAR(255) NULL;
+ALTER TABLE events ADD COLUMN uninterested_emoji VARCHAR(255) NULL;
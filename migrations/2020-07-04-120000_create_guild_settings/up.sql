@@ -0,0 +1,5 @@
+-- Your SQL goes here
+CREATE TABLE guild_settings (
+  guild_id VARCHAR(255) PRIMARY KEY,
+  language VARCHAR(255) NULL
+)
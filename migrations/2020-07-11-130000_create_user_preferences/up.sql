@@ -0,0 +1,5 @@
+-- Your SQL goes here
+CREATE TABLE user_preferences (
+  user_id VARCHAR(255) PRIMARY KEY,
+  timezone VARCHAR(255) NULL
+)
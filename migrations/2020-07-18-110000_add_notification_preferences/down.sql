@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE user_preferences
+  DROP COLUMN confirmation_dms,
+  DROP COLUMN reminder_dms,
+  DROP COLUMN dms;
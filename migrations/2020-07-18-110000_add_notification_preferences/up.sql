@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE user_preferences
+  ADD COLUMN confirmation_dms BOOLEAN NOT NULL DEFAULT TRUE,
+  ADD COLUMN reminder_dms BOOLEAN NOT NULL DEFAULT TRUE,
+  ADD COLUMN dms BOOLEAN NOT NULL DEFAULT TRUE
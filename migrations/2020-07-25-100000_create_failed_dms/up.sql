@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE failed_dms (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_id INTEGER NOT NULL,
+  user_id VARCHAR(255) NOT NULL,
+  UNIQUE (event_id, user_id)
+)
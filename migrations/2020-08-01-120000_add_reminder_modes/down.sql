@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events DROP COLUMN reminder_mode;
+ALTER TABLE guild_settings DROP COLUMN reminder_mode;
+ALTER TABLE guild_settings DROP COLUMN reminder_channel;
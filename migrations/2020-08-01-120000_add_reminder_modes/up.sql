@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN reminder_mode VARCHAR(255) NULL;
+ALTER TABLE guild_settings ADD COLUMN reminder_mode VARCHAR(255) NULL;
+ALTER TABLE guild_settings ADD COLUMN reminder_channel VARCHAR(255) NULL;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN attendee_role BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE events ADD COLUMN role_id VARCHAR(255) NULL;
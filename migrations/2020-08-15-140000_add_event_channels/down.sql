@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events DROP COLUMN channels_lead;
+ALTER TABLE events DROP COLUMN voice_channel_id;
+ALTER TABLE events DROP COLUMN text_channel_id;
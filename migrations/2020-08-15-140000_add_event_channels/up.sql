@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN channels_lead INTEGER NULL;
+ALTER TABLE events ADD COLUMN voice_channel_id VARCHAR(255) NULL;
+ALTER TABLE events ADD COLUMN text_channel_id VARCHAR(255) NULL;
@@ -0,0 +1,17 @@
+-- Your SQL goes here
+CREATE TABLE check_ins (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_id INTEGER NOT NULL,
+  user_id VARCHAR(255) NOT NULL,
+  UNIQUE (event_id, user_id)
+);
+
+CREATE TABLE attendance (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_id INTEGER NOT NULL,
+  event_name VARCHAR(255) NOT NULL,
+  event_time DATETIME NOT NULL,
+  user_id VARCHAR(255) NOT NULL,
+  rsvp BOOLEAN NOT NULL,
+  attended BOOLEAN NOT NULL
+);
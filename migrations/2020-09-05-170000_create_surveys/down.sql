@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events DROP COLUMN survey;
+ALTER TABLE user_preferences DROP COLUMN survey_dms;
+DROP TABLE surveys;
+DROP TABLE feedback;
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN survey BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE user_preferences ADD COLUMN survey_dms BOOLEAN NOT NULL DEFAULT TRUE;
+
+CREATE TABLE surveys (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_id INTEGER NOT NULL,
+  event_name VARCHAR(255) NOT NULL,
+  creator_id VARCHAR(255) NOT NULL,
+  sent_time DATETIME NOT NULL,
+  co_organizers TEXT NULL
+);
+
+CREATE TABLE feedback (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  survey_id INTEGER NOT NULL,
+  user_id VARCHAR(255) NOT NULL,
+  message_id VARCHAR(255) NOT NULL,
+  rating INTEGER NULL,
+  comment TEXT NULL
+);
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TABLE polls (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_name VARCHAR(255) NOT NULL,
+  event_desc VARCHAR(255) NULL,
+  event_loc VARCHAR(255) NULL,
+  creator_id VARCHAR(255) NOT NULL,
+  channel_id VARCHAR(255) NOT NULL,
+  message_id VARCHAR(255) NOT NULL,
+  closes_time DATETIME NOT NULL
+);
+
+CREATE TABLE poll_options (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  poll_id INTEGER NOT NULL,
+  position INTEGER NOT NULL,
+  option_time DATETIME NOT NULL
+);
@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE guild_settings ADD COLUMN block_conflicts BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE guild_settings DROP COLUMN digest_message_id;
+ALTER TABLE user_preferences DROP COLUMN digest_dms;
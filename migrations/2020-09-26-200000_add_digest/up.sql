@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE guild_settings ADD COLUMN digest_message_id VARCHAR(255) NULL;
+ALTER TABLE user_preferences ADD COLUMN digest_dms BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN tags TEXT NULL;
+
+CREATE TABLE subscriptions (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  user_id VARCHAR(255) NOT NULL,
+  tag VARCHAR(255) NOT NULL,
+  auto_rsvp BOOLEAN NOT NULL DEFAULT FALSE,
+  UNIQUE (user_id, tag)
+);
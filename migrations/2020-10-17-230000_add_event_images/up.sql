@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE events ADD COLUMN image_link VARCHAR(255) NULL;
+
+CREATE TABLE event_images (
+  event_id INTEGER PRIMARY KEY,
+  filename VARCHAR(255) NOT NULL,
+  data MEDIUMBLOB NOT NULL,
+  banner BOOLEAN NOT NULL DEFAULT TRUE
+);
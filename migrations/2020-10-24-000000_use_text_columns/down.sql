@@ -0,0 +1,25 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE events
+  MODIFY event_name VARCHAR(255) NOT NULL,
+  MODIFY event_desc VARCHAR(255) NOT NULL,
+  MODIFY event_loc VARCHAR(255) NOT NULL,
+  MODIFY organizer VARCHAR(255) NOT NULL,
+  MODIFY thumbnail_link VARCHAR(255) NOT NULL,
+  MODIFY discussion_link VARCHAR(255) NULL,
+  MODIFY image_link VARCHAR(255) NULL;
+
+ALTER TABLE proposals
+  MODIFY event_name VARCHAR(255) NOT NULL,
+  MODIFY event_desc VARCHAR(255) NOT NULL,
+  MODIFY event_loc VARCHAR(255) NOT NULL,
+  MODIFY organizer VARCHAR(255) NOT NULL,
+  MODIFY thumbnail_link VARCHAR(255) NOT NULL;
+
+ALTER TABLE polls
+  MODIFY event_name VARCHAR(255) NOT NULL,
+  MODIFY event_desc VARCHAR(255) NULL,
+  MODIFY event_loc VARCHAR(255) NULL;
+
+ALTER TABLE attendance MODIFY event_name VARCHAR(255) NOT NULL;
+
+ALTER TABLE surveys MODIFY event_name VARCHAR(255) NOT NULL;
@@ -0,0 +1,25 @@
+-- Your SQL goes here
+ALTER TABLE events
+  MODIFY event_name TEXT NOT NULL,
+  MODIFY event_desc TEXT NOT NULL,
+  MODIFY event_loc TEXT NOT NULL,
+  MODIFY organizer TEXT NOT NULL,
+  MODIFY thumbnail_link TEXT NOT NULL,
+  MODIFY discussion_link TEXT NULL,
+  MODIFY image_link TEXT NULL;
+
+ALTER TABLE proposals
+  MODIFY event_name TEXT NOT NULL,
+  MODIFY event_desc TEXT NOT NULL,
+  MODIFY event_loc TEXT NOT NULL,
+  MODIFY organizer TEXT NOT NULL,
+  MODIFY thumbnail_link TEXT NOT NULL;
+
+ALTER TABLE polls
+  MODIFY event_name TEXT NOT NULL,
+  MODIFY event_desc TEXT NULL,
+  MODIFY event_loc TEXT NULL;
+
+ALTER TABLE attendance MODIFY event_name TEXT NOT NULL;
+
+ALTER TABLE surveys MODIFY event_name TEXT NOT NULL;
@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE past_events (
+  id INTEGER AUTO_INCREMENT PRIMARY KEY,
+  event_name TEXT NOT NULL,
+  event_desc TEXT NOT NULL,
+  event_loc TEXT NOT NULL,
+  organizer TEXT NOT NULL,
+  event_time DATETIME NOT NULL,
+  thumbnail_link TEXT NOT NULL,
+  creator_id VARCHAR(255) NOT NULL,
+  interested_emoji VARCHAR(255) NULL,
+  uninterested_emoji VARCHAR(255) NULL,
+  reminder_mode VARCHAR(255) NULL,
+  attendee_role BOOLEAN NOT NULL DEFAULT FALSE,
+  channels_lead INTEGER NULL,
+  survey BOOLEAN NOT NULL DEFAULT FALSE,
+  tags TEXT NULL,
+  image_link TEXT NULL
+);
@@ -1,10 +1,15 @@
 pub mod models;
 pub mod schema;
 
+use chrono::NaiveDateTime;
 use diesel::prelude::*;
 use diesel::result::Error;
 use diesel::update;
-use models::{Event, NewEvent};
+use models::{
+    Event, EventImage, Feedback, GuildSettings, NewAttendance, NewCheckIn, NewCoOrganizer,
+    NewEvent, NewFailedDm, NewFeedback, NewPoll, NewPollOption, NewProposal, NewSubscription,
+    NewSurvey, PastEvent, Poll, Proposal, Subscription, Survey, UserPreferences,
+};
 use std::vec::Vec;
 
 /// Establish a connection to the database
@@ -20,10 +25,9 @@ pub fn insert_event(database_url: String, new_event: &NewEvent) -> Result<Event,
 
     diesel::insert_into(events)
         .values(new_event)
-        .execute(&connection)
-        .expect("Error saving event");
+        .execute(&connection)?;
 
-    events.order(id).first(&connection)
+    events.order(id.desc()).first(&connection)
 }
 
 /// Remove event
@@ -32,9 +36,55 @@ pub fn remove_event(database_url: String, event_id: i32) -> Result<usize, Error>
 
     let connection = establish_connection(database_url);
 
+    {
+        use schema::co_organizers::dsl;
+        diesel::delete(dsl::co_organizers.filter(dsl::event_id.eq(event_id)))
+            .execute(&connection)?;
+    }
+
+    {
+        use schema::check_ins::dsl;
+        diesel::delete(dsl::check_ins.filter(dsl::event_id.eq(event_id))).execute(&connection)?;
+    }
+
+    {
+        use schema::failed_dms::dsl;
+        diesel::delete(dsl::failed_dms.filter(dsl::event_id.eq(event_id))).execute(&connection)?;
+    }
+
+    {
+        use schema::event_images::dsl;
+        diesel::delete(dsl::event_images.filter(dsl::event_id.eq(event_id)))
+            .execute(&connection)?;
+    }
+
     diesel::delete(events.filter(id.eq(event_id))).execute(&connection)
 }
 
+/// Get an event by its id
+pub fn get_event_by_id(database_url: String, event_id: i32) -> Result<Event, Error> {
+    use schema::events::dsl::{events, id};
+
+    let connection = establish_connection(database_url);
+
+    events
+        .filter(id.eq(event_id))
+        .get_result::<Event>(&connection)
+}
+
+/// Update the stored fields of an event
+pub fn update_event(
+    database_url: String,
+    event_id: i32,
+    new_event: &NewEvent,
+) -> Result<usize, Error> {
+    use schema::events::dsl::{events, id};
+    let connection = establish_connection(database_url);
+
+    let target = events.filter(id.eq(event_id));
+    update(target).set(new_event).execute(&connection)
+}
+
 /// Get an event by name
 pub fn get_event_by_name(database_url: String, name: String) -> Result<Event, Error> {
     use schema::events::dsl::{event_name, events};
@@ -46,6 +96,50 @@ pub fn get_event_by_name(database_url: String, name: String) -> Result<Event, Er
         .get_result::<Event>(&connection)
 }
 
+/// Store an event that is over, so it can be cloned later
+pub fn insert_past_event(database_url: String, past_event: &PastEvent) -> Result<usize, Error> {
+    use schema::past_events::dsl::past_events;
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_into(past_events)
+        .values(past_event)
+        .execute(&connection)
+}
+
+/// Get the latest past event with a name
+pub fn get_past_event_by_name(database_url: String, name: String) -> Result<PastEvent, Error> {
+    use schema::past_events::dsl::{
+        attendee_role, channels_lead, creator_id, event_desc, event_loc, event_name, event_time,
+        image_link, interested_emoji, organizer, past_events, reminder_mode, survey, tags,
+        thumbnail_link, uninterested_emoji,
+    };
+
+    let connection = establish_connection(database_url);
+
+    past_events
+        .filter(event_name.eq(&name))
+        .order(event_time.desc())
+        .select((
+            event_name,
+            event_desc,
+            event_loc,
+            organizer,
+            event_time,
+            thumbnail_link,
+            creator_id,
+            interested_emoji,
+            uninterested_emoji,
+            reminder_mode,
+            attendee_role,
+            channels_lead,
+            survey,
+            tags,
+            image_link,
+        ))
+        .first::<PastEvent>(&connection)
+}
+
 /// Get event by its message id
 pub fn get_event_by_msg_id(database_url: String, msg_id: String) -> Result<Event, Error> {
     use schema::events::dsl::{events, message_id};
@@ -76,3 +170,796 @@ pub fn set_reminder(database_url: String, event_id: i32, state: i32) -> Result<u
         .set(reminder_sent.eq(state))
         .execute(&connection)
 }
+
+/// Insert a proposed event into the database
+pub fn insert_proposal(
+    database_url: String,
+    new_proposal: &NewProposal,
+) -> Result<Proposal, Error> {
+    use schema::proposals::dsl::{id, proposals};
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_into(proposals)
+        .values(new_proposal)
+        .execute(&connection)?;
+
+    proposals.order(id.desc()).first(&connection)
+}
+
+/// Remove a proposed event
+pub fn remove_proposal(database_url: String, proposal_id: i32) -> Result<usize, Error> {
+    use schema::proposals::dsl::{id, proposals};
+
+    let connection = establish_connection(database_url);
+
+    diesel::delete(proposals.filter(id.eq(proposal_id))).execute(&connection)
+}
+
+/// Check if a proposed event is still waiting to be approved or rejected
+pub fn proposal_exists(database_url: String, proposal_id: i32) -> Result<bool, Error> {
+    use schema::proposals::dsl::{id, proposals};
+
+    let connection = establish_connection(database_url);
+
+    proposals
+        .filter(id.eq(proposal_id))
+        .count()
+        .get_result::<i64>(&connection)
+        .map(|count| count > 0)
+}
+
+/// Get a proposed event by name
+pub fn get_proposal_by_name(database_url: String, name: String) -> Result<Proposal, Error> {
+    use schema::proposals::dsl::{event_name, proposals};
+
+    let connection = establish_connection(database_url);
+
+    proposals
+        .filter(event_name.eq(&name))
+        .get_result::<Proposal>(&connection)
+}
+
+/// Get a proposed event by its moderation message id
+pub fn get_proposal_by_msg_id(database_url: String, msg_id: String) -> Result<Proposal, Error> {
+    use schema::proposals::dsl::{message_id, proposals};
+
+    let connection = establish_connection(database_url);
+
+    proposals
+        .filter(message_id.eq(&msg_id))
+        .get_result::<Proposal>(&connection)
+}
+
+/// Mark an event that was waiting to be posted as posted
+pub fn set_published(database_url: String, event_id: i32, msg_id: String) -> Result<usize, Error> {
+    use schema::events::dsl::{events, id, message_id, publish_time};
+    let connection = establish_connection(database_url);
+
+    let target = events.filter(id.eq(event_id));
+    update(target)
+        .set((
+            message_id.eq(msg_id),
+            publish_time.eq(None::<NaiveDateTime>),
+        ))
+        .execute(&connection)
+}
+
+/// Set the attendee role of an event
+pub fn set_event_role(
+    database_url: String,
+    event_id: i32,
+    role: Option<String>,
+) -> Result<usize, Error> {
+    use schema::events::dsl::{events, id, role_id};
+    let connection = establish_connection(database_url);
+
+    let target = events.filter(id.eq(event_id));
+    update(target).set(role_id.eq(role)).execute(&connection)
+}
+
+/// Remove every tag from an event, which `update_event` can not do since it skips empty fields
+pub fn clear_event_tags(database_url: String, event_id: i32) -> Result<usize, Error> {
+    use schema::events::dsl::{events, id, tags};
+    let connection = establish_connection(database_url);
+
+    let target = events.filter(id.eq(event_id));
+    update(target)
+        .set(tags.eq(None::<String>))
+        .execute(&connection)
+}
+
+/// Set the voice and text channels of an event
+pub fn set_event_channels(
+    database_url: String,
+    event_id: i32,
+    voice: Option<String>,
+    text: Option<String>,
+) -> Result<usize, Error> {
+    use schema::events::dsl::{events, id, text_channel_id, voice_channel_id};
+    let connection = establish_connection(database_url);
+
+    let target = events.filter(id.eq(event_id));
+    update(target)
+        .set((voice_channel_id.eq(voice), text_channel_id.eq(text)))
+        .execute(&connection)
+}
+
+/// Set the link to the discussion of an event
+pub fn set_event_discussion(
+    database_url: String,
+    event_id: i32,
+    link: Option<String>,
+) -> Result<usize, Error> {
+    use schema::events::dsl::{discussion_link, events, id};
+    let connection = establish_connection(database_url);
+
+    let target = events.filter(id.eq(event_id));
+    update(target)
+        .set(discussion_link.eq(link))
+        .execute(&connection)
+}
+
+/// Store the image of an event, replacing any it already had
+pub fn insert_event_image(database_url: String, image: &EventImage) -> Result<usize, Error> {
+    use schema::event_images::dsl::event_images;
+
+    let connection = establish_connection(database_url);
+
+    diesel::replace_into(event_images)
+        .values(image)
+        .execute(&connection)
+}
+
+/// Get the stored image of an event
+pub fn get_event_image(database_url: String, event: i32) -> Result<EventImage, Error> {
+    use schema::event_images::dsl::{event_id, event_images};
+
+    let connection = establish_connection(database_url);
+
+    event_images
+        .filter(event_id.eq(event))
+        .get_result::<EventImage>(&connection)
+}
+
+/// Remove the stored image of an event
+pub fn remove_event_image(database_url: String, event: i32) -> Result<usize, Error> {
+    use schema::event_images::dsl::{event_id, event_images};
+
+    let connection = establish_connection(database_url);
+
+    diesel::delete(event_images.filter(event_id.eq(event))).execute(&connection)
+}
+
+/// Set the organizer of an event
+pub fn set_event_creator(
+    database_url: String,
+    event_id: i32,
+    user: String,
+) -> Result<usize, Error> {
+    use schema::events::dsl::{creator_id, events, id};
+    let connection = establish_connection(database_url);
+
+    let target = events.filter(id.eq(event_id));
+    update(target).set(creator_id.eq(user)).execute(&connection)
+}
+
+/// Add a co-organizer to an event
+pub fn add_co_organizer(database_url: String, event: i32, user: String) -> Result<usize, Error> {
+    use schema::co_organizers::dsl::co_organizers;
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_into(co_organizers)
+        .values(&NewCoOrganizer {
+            event_id: event,
+            user_id: user,
+        })
+        .execute(&connection)
+}
+
+/// Remove a co-organizer from an event
+pub fn remove_co_organizer(database_url: String, event: i32, user: String) -> Result<usize, Error> {
+    use schema::co_organizers::dsl::{co_organizers, event_id, user_id};
+
+    let connection = establish_connection(database_url);
+
+    diesel::delete(co_organizers.filter(event_id.eq(event).and(user_id.eq(user))))
+        .execute(&connection)
+}
+
+/// Get the user ids of the co-organizers of an event
+pub fn get_co_organizers(database_url: String, event: i32) -> Result<Vec<String>, Error> {
+    use schema::co_organizers::dsl::{co_organizers, event_id, user_id};
+
+    let connection = establish_connection(database_url);
+
+    co_organizers
+        .filter(event_id.eq(event))
+        .select(user_id)
+        .load(&connection)
+}
+
+/// Check a user in to an event
+///
+/// Returns `true` if the user had not checked in yet.
+pub fn add_check_in(database_url: String, event: i32, user: String) -> Result<bool, Error> {
+    use schema::check_ins::dsl::check_ins;
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_or_ignore_into(check_ins)
+        .values(&NewCheckIn {
+            event_id: event,
+            user_id: user,
+        })
+        .execute(&connection)
+        .map(|inserted| inserted > 0)
+}
+
+/// Get the user ids that checked in to an event
+pub fn get_check_ins(database_url: String, event: i32) -> Result<Vec<String>, Error> {
+    use schema::check_ins::dsl::{check_ins, event_id, user_id};
+
+    let connection = establish_connection(database_url);
+
+    check_ins
+        .filter(event_id.eq(event))
+        .select(user_id)
+        .load(&connection)
+}
+
+/// Record who was interested in and who attended an event
+pub fn insert_attendance(database_url: String, records: &[NewAttendance]) -> Result<usize, Error> {
+    use schema::attendance::dsl::attendance;
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_into(attendance)
+        .values(records)
+        .execute(&connection)
+}
+
+/// Get the RSVP and attendance of a user for every past event, as `(rsvp, attended)` pairs
+pub fn get_user_attendance(database_url: String, user: String) -> Result<Vec<(bool, bool)>, Error> {
+    use schema::attendance::dsl::{attendance, attended, rsvp, user_id};
+
+    let connection = establish_connection(database_url);
+
+    attendance
+        .filter(user_id.eq(user))
+        .select((rsvp, attended))
+        .load(&connection)
+}
+
+/// Get how many users checked in to a past event
+pub fn get_attended_count(database_url: String, event: i32) -> Result<i64, Error> {
+    use schema::attendance::dsl::{attendance, attended, event_id};
+
+    let connection = establish_connection(database_url);
+
+    attendance
+        .filter(event_id.eq(event).and(attended.eq(true)))
+        .count()
+        .get_result(&connection)
+}
+
+/// Insert a survey about an event
+pub fn insert_survey(database_url: String, new_survey: &NewSurvey) -> Result<Survey, Error> {
+    use schema::surveys::dsl::{id, surveys};
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_into(surveys)
+        .values(new_survey)
+        .execute(&connection)?;
+
+    surveys.order(id.desc()).first(&connection)
+}
+
+/// Get the latest survey about an event by the event's name
+pub fn get_survey_by_event_name(database_url: String, name: String) -> Result<Survey, Error> {
+    use schema::surveys::dsl::{event_name, id, surveys};
+
+    let connection = establish_connection(database_url);
+
+    surveys
+        .filter(event_name.eq(name))
+        .order(id.desc())
+        .first(&connection)
+}
+
+/// Get a survey by its id
+pub fn get_survey_by_id(database_url: String, survey: i32) -> Result<Survey, Error> {
+    use schema::surveys::dsl::{id, surveys};
+
+    let connection = establish_connection(database_url);
+
+    surveys.filter(id.eq(survey)).get_result(&connection)
+}
+
+/// Insert a survey sent to an attendee
+pub fn insert_feedback(database_url: String, new_feedback: &NewFeedback) -> Result<usize, Error> {
+    use schema::feedback::dsl::feedback;
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_into(feedback)
+        .values(new_feedback)
+        .execute(&connection)
+}
+
+/// Get the feedback of an attendee by the id of the survey DM
+pub fn get_feedback_by_msg_id(database_url: String, msg_id: String) -> Result<Feedback, Error> {
+    use schema::feedback::dsl::{feedback, message_id};
+
+    let connection = establish_connection(database_url);
+
+    feedback
+        .filter(message_id.eq(msg_id))
+        .get_result(&connection)
+}
+
+/// Get the surveys sent to a user that they have not commented on yet, latest first
+pub fn get_uncommented_feedback(
+    database_url: String,
+    user: String,
+) -> Result<Vec<Feedback>, Error> {
+    use schema::feedback::dsl::{comment, feedback, id, user_id};
+
+    let connection = establish_connection(database_url);
+
+    feedback
+        .filter(user_id.eq(user).and(comment.is_null()))
+        .order(id.desc())
+        .load(&connection)
+}
+
+/// Get every response to a survey
+pub fn get_survey_feedback(database_url: String, survey: i32) -> Result<Vec<Feedback>, Error> {
+    use schema::feedback::dsl::{feedback, survey_id};
+
+    let connection = establish_connection(database_url);
+
+    feedback.filter(survey_id.eq(survey)).load(&connection)
+}
+
+/// Set the rating an attendee gave an event
+pub fn set_feedback_rating(
+    database_url: String,
+    feedback_id: i32,
+    value: i32,
+) -> Result<usize, Error> {
+    use schema::feedback::dsl::{feedback, id, rating};
+    let connection = establish_connection(database_url);
+
+    let target = feedback.filter(id.eq(feedback_id));
+    update(target).set(rating.eq(value)).execute(&connection)
+}
+
+/// Set the comments an attendee had about an event
+pub fn set_feedback_comment(
+    database_url: String,
+    feedback_id: i32,
+    value: String,
+) -> Result<usize, Error> {
+    use schema::feedback::dsl::{comment, feedback, id};
+    let connection = establish_connection(database_url);
+
+    let target = feedback.filter(id.eq(feedback_id));
+    update(target).set(comment.eq(value)).execute(&connection)
+}
+
+/// Record that a user could not be sent a DM about an event
+///
+/// Returns `true` if this is the first failed DM to the user about the event.
+pub fn record_failed_dm(database_url: String, event: i32, user: String) -> Result<bool, Error> {
+    use schema::failed_dms::dsl::failed_dms;
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_or_ignore_into(failed_dms)
+        .values(&NewFailedDm {
+            event_id: event,
+            user_id: user,
+        })
+        .execute(&connection)
+        .map(|inserted| inserted > 0)
+}
+
+/// Forget a failed DM once the user can be reached again
+pub fn clear_failed_dm(database_url: String, event: i32, user: String) -> Result<usize, Error> {
+    use schema::failed_dms::dsl::{event_id, failed_dms, user_id};
+
+    let connection = establish_connection(database_url);
+
+    diesel::delete(failed_dms.filter(event_id.eq(event).and(user_id.eq(user)))).execute(&connection)
+}
+
+/// Forget all failed DMs about an event
+pub fn remove_failed_dms(database_url: String, event: i32) -> Result<usize, Error> {
+    use schema::failed_dms::dsl::{event_id, failed_dms};
+
+    let connection = establish_connection(database_url);
+
+    diesel::delete(failed_dms.filter(event_id.eq(event))).execute(&connection)
+}
+
+/// Get the user ids that could not be sent a DM about an event
+pub fn get_failed_dms(database_url: String, event: i32) -> Result<Vec<String>, Error> {
+    use schema::failed_dms::dsl::{event_id, failed_dms, user_id};
+
+    let connection = establish_connection(database_url);
+
+    failed_dms
+        .filter(event_id.eq(event))
+        .select(user_id)
+        .load(&connection)
+}
+
+/// Get the settings of a guild
+pub fn get_guild_settings(database_url: String, guild: String) -> Result<GuildSettings, Error> {
+    use schema::guild_settings::dsl::{guild_id, guild_settings};
+
+    let connection = establish_connection(database_url);
+
+    guild_settings
+        .filter(guild_id.eq(&guild))
+        .get_result::<GuildSettings>(&connection)
+}
+
+/// Make sure a guild has a settings row so it can be updated
+fn ensure_guild_settings(connection: &MysqlConnection, guild: &str) -> Result<usize, Error> {
+    use schema::guild_settings::dsl::guild_settings;
+
+    diesel::insert_or_ignore_into(guild_settings)
+        .values(&GuildSettings::new(guild.to_string()))
+        .execute(connection)
+}
+
+/// Set the language of a guild
+pub fn set_guild_language(
+    database_url: String,
+    guild: String,
+    lang: String,
+) -> Result<usize, Error> {
+    use schema::guild_settings::dsl::{guild_id, guild_settings, language};
+    let connection = establish_connection(database_url);
+
+    ensure_guild_settings(&connection, &guild)?;
+
+    let target = guild_settings.filter(guild_id.eq(&guild));
+    update(target).set(language.eq(lang)).execute(&connection)
+}
+
+/// Get the preferences of a user
+pub fn get_user_preferences(database_url: String, user: String) -> Result<UserPreferences, Error> {
+    use schema::user_preferences::dsl::{user_id, user_preferences};
+
+    let connection = establish_connection(database_url);
+
+    user_preferences
+        .filter(user_id.eq(&user))
+        .get_result::<UserPreferences>(&connection)
+}
+
+/// Make sure a user has a preferences row so it can be updated
+fn ensure_user_preferences(connection: &MysqlConnection, user: &str) -> Result<usize, Error> {
+    use schema::user_preferences::dsl::user_preferences;
+
+    diesel::insert_or_ignore_into(user_preferences)
+        .values(&UserPreferences::new(user.to_string()))
+        .execute(connection)
+}
+
+/// Set the timezone of a user
+pub fn set_user_timezone(
+    database_url: String,
+    user: String,
+    tz: Option<String>,
+) -> Result<usize, Error> {
+    use schema::user_preferences::dsl::{timezone, user_id, user_preferences};
+    let connection = establish_connection(database_url);
+
+    ensure_user_preferences(&connection, &user)?;
+
+    let target = user_preferences.filter(user_id.eq(&user));
+    update(target).set(timezone.eq(tz)).execute(&connection)
+}
+
+/// Set which DMs a user receives, leaving out any setting that is `None`
+pub fn set_user_notifications(
+    database_url: String,
+    user: String,
+    confirmations: Option<bool>,
+    reminders: Option<bool>,
+    digest: Option<bool>,
+    surveys: Option<bool>,
+    all: Option<bool>,
+) -> Result<UserPreferences, Error> {
+    use schema::user_preferences::dsl::{
+        confirmation_dms, digest_dms, dms, reminder_dms, survey_dms, user_id, user_preferences,
+    };
+    let connection = establish_connection(database_url);
+
+    ensure_user_preferences(&connection, &user)?;
+
+    let target = user_preferences.filter(user_id.eq(&user));
+    if let Some(confirmations) = confirmations {
+        update(target)
+            .set(confirmation_dms.eq(confirmations))
+            .execute(&connection)?;
+    }
+    if let Some(reminders) = reminders {
+        update(target)
+            .set(reminder_dms.eq(reminders))
+            .execute(&connection)?;
+    }
+    if let Some(digest) = digest {
+        update(target)
+            .set(digest_dms.eq(digest))
+            .execute(&connection)?;
+    }
+    if let Some(surveys) = surveys {
+        update(target)
+            .set(survey_dms.eq(surveys))
+            .execute(&connection)?;
+    }
+    if let Some(all) = all {
+        update(target).set(dms.eq(all)).execute(&connection)?;
+    }
+
+    target.get_result::<UserPreferences>(&connection)
+}
+
+/// Get the ids of the users who want the weekly digest as a DM
+pub fn get_digest_users(database_url: String) -> Result<Vec<String>, Error> {
+    use schema::user_preferences::dsl::{digest_dms, dms, user_id, user_preferences};
+
+    let connection = establish_connection(database_url);
+
+    user_preferences
+        .filter(digest_dms.eq(true).and(dms.eq(true)))
+        .select(user_id)
+        .load(&connection)
+}
+
+/// Set how reminders are sent in a guild, and optionally the channel they are posted in
+pub fn set_guild_reminders(
+    database_url: String,
+    guild: String,
+    mode: String,
+    channel: Option<String>,
+) -> Result<usize, Error> {
+    use schema::guild_settings::dsl::{guild_id, guild_settings, reminder_channel, reminder_mode};
+    let connection = establish_connection(database_url);
+
+    ensure_guild_settings(&connection, &guild)?;
+
+    let target = guild_settings.filter(guild_id.eq(&guild));
+    if let Some(channel) = channel {
+        update(target)
+            .set(reminder_channel.eq(channel))
+            .execute(&connection)?;
+    }
+
+    update(target)
+        .set(reminder_mode.eq(mode))
+        .execute(&connection)
+}
+
+/// Set whether events that overlap another event can be created in a guild
+pub fn set_guild_conflicts(
+    database_url: String,
+    guild: String,
+    block: bool,
+) -> Result<usize, Error> {
+    use schema::guild_settings::dsl::{block_conflicts, guild_id, guild_settings};
+    let connection = establish_connection(database_url);
+
+    ensure_guild_settings(&connection, &guild)?;
+
+    let target = guild_settings.filter(guild_id.eq(&guild));
+    update(target)
+        .set(block_conflicts.eq(block))
+        .execute(&connection)
+}
+
+/// Set the message id of the pinned weekly digest of a guild
+pub fn set_guild_digest_message(
+    database_url: String,
+    guild: String,
+    message: Option<String>,
+) -> Result<usize, Error> {
+    use schema::guild_settings::dsl::{digest_message_id, guild_id, guild_settings};
+    let connection = establish_connection(database_url);
+
+    ensure_guild_settings(&connection, &guild)?;
+
+    let target = guild_settings.filter(guild_id.eq(&guild));
+    update(target)
+        .set(digest_message_id.eq(message))
+        .execute(&connection)
+}
+
+/// Set the message id of the pinned calendar of a guild
+pub fn set_guild_calendar_message(
+    database_url: String,
+    guild: String,
+    message: Option<String>,
+) -> Result<usize, Error> {
+    use schema::guild_settings::dsl::{calendar_message_id, guild_id, guild_settings};
+    let connection = establish_connection(database_url);
+
+    ensure_guild_settings(&connection, &guild)?;
+
+    let target = guild_settings.filter(guild_id.eq(&guild));
+    update(target)
+        .set(calendar_message_id.eq(message))
+        .execute(&connection)
+}
+
+/// Get the events starting strictly between two times
+pub fn get_events_between(
+    database_url: String,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<Vec<Event>, Error> {
+    use schema::events::dsl::{event_time, events};
+
+    let connection = establish_connection(database_url);
+
+    events
+        .filter(event_time.gt(start).and(event_time.lt(end)))
+        .order(event_time)
+        .load(&connection)
+}
+
+/// Insert a poll and the times being voted on
+pub fn insert_poll(
+    database_url: String,
+    new_poll: &NewPoll,
+    option_times: &[NaiveDateTime],
+) -> Result<Poll, Error> {
+    use schema::polls::dsl::{id, polls};
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_into(polls)
+        .values(new_poll)
+        .execute(&connection)?;
+
+    let poll: Poll = polls.order(id.desc()).first(&connection)?;
+
+    let options: Vec<NewPollOption> = option_times
+        .iter()
+        .enumerate()
+        .map(|(position, option_time)| NewPollOption {
+            poll_id: poll.id,
+            position: position as i32,
+            option_time: *option_time,
+        })
+        .collect();
+
+    diesel::insert_into(schema::poll_options::table)
+        .values(&options)
+        .execute(&connection)?;
+
+    Ok(poll)
+}
+
+/// Get a poll by its id
+pub fn get_poll_by_id(database_url: String, poll_id: i32) -> Result<Poll, Error> {
+    use schema::polls::dsl::{id, polls};
+
+    let connection = establish_connection(database_url);
+
+    polls.filter(id.eq(poll_id)).get_result(&connection)
+}
+
+/// Get all polls that have not been tallied yet
+pub fn get_all_polls(database_url: String) -> Result<Vec<Poll>, Error> {
+    use schema::polls::dsl::{closes_time, polls};
+
+    let connection = establish_connection(database_url);
+
+    polls.order(closes_time).load(&connection)
+}
+
+/// Get the times being voted on in a poll, as `(position, time)` pairs in the order they were given
+pub fn get_poll_options(
+    database_url: String,
+    poll: i32,
+) -> Result<Vec<(i32, NaiveDateTime)>, Error> {
+    use schema::poll_options::dsl::{option_time, poll_id, poll_options, position};
+
+    let connection = establish_connection(database_url);
+
+    poll_options
+        .filter(poll_id.eq(poll))
+        .order(position)
+        .select((position, option_time))
+        .load(&connection)
+}
+
+/// Remove a poll and its options
+pub fn remove_poll(database_url: String, poll: i32) -> Result<usize, Error> {
+    use schema::polls::dsl::{id, polls};
+
+    let connection = establish_connection(database_url);
+
+    {
+        use schema::poll_options::dsl;
+        diesel::delete(dsl::poll_options.filter(dsl::poll_id.eq(poll))).execute(&connection)?;
+    }
+
+    diesel::delete(polls.filter(id.eq(poll))).execute(&connection)
+}
+
+/// Subscribe a user to a tag, updating whether they are auto-RSVP'd if they already are
+pub fn add_subscription(
+    database_url: String,
+    new_subscription: &NewSubscription,
+) -> Result<usize, Error> {
+    use schema::subscriptions::dsl::{auto_rsvp, subscriptions, tag, user_id};
+
+    let connection = establish_connection(database_url);
+
+    diesel::insert_or_ignore_into(subscriptions)
+        .values(new_subscription)
+        .execute(&connection)?;
+
+    let target = subscriptions.filter(
+        user_id
+            .eq(&new_subscription.user_id)
+            .and(tag.eq(&new_subscription.tag)),
+    );
+    update(target)
+        .set(auto_rsvp.eq(new_subscription.auto_rsvp))
+        .execute(&connection)
+}
+
+/// Unsubscribe a user from a tag
+pub fn remove_subscription(
+    database_url: String,
+    user: String,
+    tag_name: String,
+) -> Result<usize, Error> {
+    use schema::subscriptions::dsl::{subscriptions, tag, user_id};
+
+    let connection = establish_connection(database_url);
+
+    diesel::delete(subscriptions.filter(user_id.eq(user).and(tag.eq(tag_name))))
+        .execute(&connection)
+}
+
+/// Get the tags a user is subscribed to
+pub fn get_user_subscriptions(
+    database_url: String,
+    user: String,
+) -> Result<Vec<Subscription>, Error> {
+    use schema::subscriptions::dsl::{auto_rsvp, subscriptions, tag, user_id};
+
+    let connection = establish_connection(database_url);
+
+    subscriptions
+        .filter(user_id.eq(user))
+        .order(tag)
+        .select((user_id, tag, auto_rsvp))
+        .load(&connection)
+}
+
+/// Get the subscriptions to any of a list of tags
+pub fn get_tag_subscriptions(
+    database_url: String,
+    tags: &[String],
+) -> Result<Vec<Subscription>, Error> {
+    use schema::subscriptions::dsl::{auto_rsvp, subscriptions, tag, user_id};
+
+    let connection = establish_connection(database_url);
+
+    subscriptions
+        .filter(tag.eq_any(tags))
+        .select((user_id, tag, auto_rsvp))
+        .load(&connection)
+}
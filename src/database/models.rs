@@ -1,4 +1,8 @@
-use super::schema::events;
+use super::schema::{
+    attendance, check_ins, co_organizers, event_images, events, failed_dms, feedback,
+    guild_settings, past_events, poll_options, polls, proposals, subscriptions, surveys,
+    user_preferences,
+};
 use chrono::NaiveDateTime;
 
 #[derive(Queryable, Clone, Debug)]
@@ -21,6 +25,34 @@ pub struct Event {
     pub thumbnail_link: String,
     /// Reminder sent tracker
     pub reminder_sent: i32,
+    /// Discord id of the user who created the event
+    pub creator_id: String,
+    /// Time to post the event at, `None` once it has been posted
+    pub publish_time: Option<NaiveDateTime>,
+    /// Emoji to react with to receive reminders, `None` to use the configured emoji
+    pub interested_emoji: Option<String>,
+    /// Emoji to react with when not interested, `None` to use the configured emoji
+    pub uninterested_emoji: Option<String>,
+    /// How reminders are sent, `None` to use the guild's mode
+    pub reminder_mode: Option<String>,
+    /// Whether a role is given to everyone interested in the event
+    pub attendee_role: bool,
+    /// Discord id of the attendee role, once it has been created
+    pub role_id: Option<String>,
+    /// Minutes before the event to create its channels, 0 for when it is posted, `None` for no channels
+    pub channels_lead: Option<i32>,
+    /// Discord id of the event's voice channel, once it has been created
+    pub voice_channel_id: Option<String>,
+    /// Discord id of the event's text channel, once it has been created
+    pub text_channel_id: Option<String>,
+    /// Link to the message starting the event's discussion, once it has been posted
+    pub discussion_link: Option<String>,
+    /// Whether attendees are sent a survey after the event
+    pub survey: bool,
+    /// Comma separated tags, like `gaming,social`
+    pub tags: Option<String>,
+    /// Banner image shown across the announcement
+    pub image_link: Option<String>,
 }
 
 impl Into<NewEvent> for Event {
@@ -32,13 +64,27 @@ impl Into<NewEvent> for Event {
             event_loc: self.event_loc.clone(),
             event_time: self.event_time.clone(),
             message_id: self.message_id.clone(),
-            thumbnail_link: self.message_id.clone(),
+            thumbnail_link: self.thumbnail_link.clone(),
             reminder_sent: self.reminder_sent,
+            creator_id: self.creator_id.clone(),
+            publish_time: self.publish_time,
+            interested_emoji: self.interested_emoji.clone(),
+            uninterested_emoji: self.uninterested_emoji.clone(),
+            reminder_mode: self.reminder_mode.clone(),
+            attendee_role: self.attendee_role,
+            role_id: self.role_id.clone(),
+            channels_lead: self.channels_lead,
+            voice_channel_id: self.voice_channel_id.clone(),
+            text_channel_id: self.text_channel_id.clone(),
+            discussion_link: self.discussion_link.clone(),
+            survey: self.survey,
+            tags: self.tags.clone(),
+            image_link: self.image_link.clone(),
         }
     }
 }
 
-#[derive(Insertable, Clone, Debug)]
+#[derive(Insertable, AsChangeset, Clone, Debug)]
 #[table_name = "events"]
 pub struct NewEvent {
     /// Event name
@@ -57,4 +103,448 @@ pub struct NewEvent {
     pub thumbnail_link: String,
     /// Reminder sent tracker
     pub reminder_sent: i32,
+    /// Discord id of the user who created the event
+    pub creator_id: String,
+    /// Time to post the event at, `None` once it has been posted
+    pub publish_time: Option<NaiveDateTime>,
+    /// Emoji to react with to receive reminders, `None` to use the configured emoji
+    pub interested_emoji: Option<String>,
+    /// Emoji to react with when not interested, `None` to use the configured emoji
+    pub uninterested_emoji: Option<String>,
+    /// How reminders are sent, `None` to use the guild's mode
+    pub reminder_mode: Option<String>,
+    /// Whether a role is given to everyone interested in the event
+    pub attendee_role: bool,
+    /// Discord id of the attendee role, once it has been created
+    pub role_id: Option<String>,
+    /// Minutes before the event to create its channels, 0 for when it is posted, `None` for no channels
+    pub channels_lead: Option<i32>,
+    /// Discord id of the event's voice channel, once it has been created
+    pub voice_channel_id: Option<String>,
+    /// Discord id of the event's text channel, once it has been created
+    pub text_channel_id: Option<String>,
+    /// Link to the message starting the event's discussion, once it has been posted
+    pub discussion_link: Option<String>,
+    /// Whether attendees are sent a survey after the event
+    pub survey: bool,
+    /// Comma separated tags, like `gaming,social`
+    pub tags: Option<String>,
+    /// Banner image shown across the announcement
+    pub image_link: Option<String>,
+}
+
+/// An image attached to an event, kept for as long as the event to upload with its announcement
+#[derive(Queryable, Insertable, Clone)]
+#[table_name = "event_images"]
+pub struct EventImage {
+    /// Event ID, 0 while the event is a draft
+    pub event_id: i32,
+    /// Name of the uploaded file
+    pub filename: String,
+    /// Image file contents
+    pub data: Vec<u8>,
+    /// Whether the image is shown as a banner rather than as the thumbnail
+    pub banner: bool,
+}
+
+/// An event that is over, kept so it can be run again with `clone`
+///
+/// Only what carries over to a new run is kept, not its message, role or channels.
+#[derive(Queryable, Insertable, Clone, Debug)]
+#[table_name = "past_events"]
+pub struct PastEvent {
+    /// Event name
+    pub event_name: String,
+    /// Event long description
+    pub event_desc: String,
+    /// Event location
+    pub event_loc: String,
+    /// Event organizer
+    pub organizer: String,
+    /// Event datetime
+    pub event_time: NaiveDateTime,
+    /// Event message thumbnail link
+    pub thumbnail_link: String,
+    /// Discord id of the user who created the event
+    pub creator_id: String,
+    /// Emoji to react with to receive reminders, `None` to use the configured emoji
+    pub interested_emoji: Option<String>,
+    /// Emoji to react with when not interested, `None` to use the configured emoji
+    pub uninterested_emoji: Option<String>,
+    /// How reminders are sent, `None` to use the guild's mode
+    pub reminder_mode: Option<String>,
+    /// Whether a role is given to everyone interested in the event
+    pub attendee_role: bool,
+    /// Minutes before the event to create its channels, 0 for when it is posted, `None` for no channels
+    pub channels_lead: Option<i32>,
+    /// Whether attendees are sent a survey after the event
+    pub survey: bool,
+    /// Comma separated tags, like `gaming,social`
+    pub tags: Option<String>,
+    /// Banner image shown across the announcement
+    pub image_link: Option<String>,
+}
+
+impl From<Event> for PastEvent {
+    fn from(event: Event) -> Self {
+        PastEvent {
+            event_name: event.event_name,
+            event_desc: event.event_desc,
+            event_loc: event.event_loc,
+            organizer: event.organizer,
+            event_time: event.event_time,
+            thumbnail_link: event.thumbnail_link,
+            creator_id: event.creator_id,
+            interested_emoji: event.interested_emoji,
+            uninterested_emoji: event.uninterested_emoji,
+            reminder_mode: event.reminder_mode,
+            attendee_role: event.attendee_role,
+            channels_lead: event.channels_lead,
+            survey: event.survey,
+            tags: event.tags,
+            image_link: event.image_link,
+        }
+    }
+}
+
+impl From<PastEvent> for NewEvent {
+    fn from(past_event: PastEvent) -> Self {
+        NewEvent {
+            event_name: past_event.event_name,
+            event_desc: past_event.event_desc,
+            event_loc: past_event.event_loc,
+            organizer: past_event.organizer,
+            event_time: past_event.event_time,
+            message_id: String::new(),
+            thumbnail_link: past_event.thumbnail_link,
+            reminder_sent: 0,
+            creator_id: past_event.creator_id,
+            publish_time: None,
+            interested_emoji: past_event.interested_emoji,
+            uninterested_emoji: past_event.uninterested_emoji,
+            reminder_mode: past_event.reminder_mode,
+            attendee_role: past_event.attendee_role,
+            role_id: None,
+            channels_lead: past_event.channels_lead,
+            voice_channel_id: None,
+            text_channel_id: None,
+            discussion_link: None,
+            survey: past_event.survey,
+            tags: past_event.tags,
+            image_link: past_event.image_link,
+        }
+    }
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct Proposal {
+    /// Proposal ID
+    pub id: i32,
+    /// Event name
+    pub event_name: String,
+    /// Event long description
+    pub event_desc: String,
+    /// Event location
+    pub event_loc: String,
+    /// Event organizer
+    pub organizer: String,
+    /// Event datetime
+    pub event_time: NaiveDateTime,
+    /// Event message thumbnail link
+    pub thumbnail_link: String,
+    /// Discord id of the user who proposed the event
+    pub proposer_id: String,
+    /// Moderation channel message id
+    pub message_id: String,
+}
+
+impl From<Proposal> for NewEvent {
+    fn from(proposal: Proposal) -> Self {
+        NewEvent {
+            event_name: proposal.event_name,
+            event_desc: proposal.event_desc,
+            event_loc: proposal.event_loc,
+            organizer: proposal.organizer,
+            event_time: proposal.event_time,
+            message_id: String::new(),
+            thumbnail_link: proposal.thumbnail_link,
+            reminder_sent: 0,
+            creator_id: proposal.proposer_id,
+            publish_time: None,
+            interested_emoji: None,
+            uninterested_emoji: None,
+            reminder_mode: None,
+            attendee_role: false,
+            role_id: None,
+            channels_lead: None,
+            voice_channel_id: None,
+            text_channel_id: None,
+            discussion_link: None,
+            survey: false,
+            tags: None,
+            image_link: None,
+        }
+    }
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "proposals"]
+pub struct NewProposal {
+    /// Event name
+    pub event_name: String,
+    /// Event long description
+    pub event_desc: String,
+    /// Event location
+    pub event_loc: String,
+    /// Event organizer
+    pub organizer: String,
+    /// Event datetime
+    pub event_time: NaiveDateTime,
+    /// Event message thumbnail link
+    pub thumbnail_link: String,
+    /// Discord id of the user who proposed the event
+    pub proposer_id: String,
+    /// Moderation channel message id
+    pub message_id: String,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "co_organizers"]
+pub struct NewCoOrganizer {
+    /// Event ID
+    pub event_id: i32,
+    /// Discord id of the co-organizer
+    pub user_id: String,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "check_ins"]
+pub struct NewCheckIn {
+    /// Event ID
+    pub event_id: i32,
+    /// Discord id of the user who checked in
+    pub user_id: String,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "attendance"]
+pub struct NewAttendance {
+    /// Event ID, the event itself is removed after it is over
+    pub event_id: i32,
+    /// Event name
+    pub event_name: String,
+    /// Event time
+    pub event_time: NaiveDateTime,
+    /// Discord id of the user
+    pub user_id: String,
+    /// Whether the user said they were interested
+    pub rsvp: bool,
+    /// Whether the user checked in
+    pub attended: bool,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "failed_dms"]
+pub struct NewFailedDm {
+    /// Event ID
+    pub event_id: i32,
+    /// Discord id of the user who could not be sent a DM
+    pub user_id: String,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct Subscription {
+    /// Discord id of the subscribed user
+    pub user_id: String,
+    /// Tag subscribed to
+    pub tag: String,
+    /// Whether the user gets reminders for events with the tag without reacting
+    pub auto_rsvp: bool,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "subscriptions"]
+pub struct NewSubscription {
+    /// Discord id of the subscribed user
+    pub user_id: String,
+    /// Tag subscribed to
+    pub tag: String,
+    /// Whether the user gets reminders for events with the tag without reacting
+    pub auto_rsvp: bool,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct Survey {
+    /// Survey ID
+    pub id: i32,
+    /// Event ID, the event itself is removed after it is over
+    pub event_id: i32,
+    /// Event name
+    pub event_name: String,
+    /// Discord id of the event's organizer
+    pub creator_id: String,
+    /// When the survey was sent
+    pub sent_time: NaiveDateTime,
+    /// Discord ids of the event's co-organizers, comma separated, kept since the event is removed
+    pub co_organizers: Option<String>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "surveys"]
+pub struct NewSurvey {
+    /// Event ID
+    pub event_id: i32,
+    /// Event name
+    pub event_name: String,
+    /// Discord id of the event's organizer
+    pub creator_id: String,
+    /// When the survey was sent
+    pub sent_time: NaiveDateTime,
+    /// Discord ids of the event's co-organizers, comma separated, kept since the event is removed
+    pub co_organizers: Option<String>,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct Feedback {
+    /// Feedback ID
+    pub id: i32,
+    /// Survey ID
+    pub survey_id: i32,
+    /// Discord id of the attendee
+    pub user_id: String,
+    /// DM message id of the survey
+    pub message_id: String,
+    /// Rating from 1 to 5, once the attendee has rated the event
+    pub rating: Option<i32>,
+    /// Comments, once the attendee has replied
+    pub comment: Option<String>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "feedback"]
+pub struct NewFeedback {
+    /// Survey ID
+    pub survey_id: i32,
+    /// Discord id of the attendee
+    pub user_id: String,
+    /// DM message id of the survey
+    pub message_id: String,
+}
+
+#[derive(Queryable, Clone, Debug)]
+pub struct Poll {
+    /// Poll ID
+    pub id: i32,
+    /// Name of the event being planned
+    pub event_name: String,
+    /// Description to fill into the draft, if one was given
+    pub event_desc: Option<String>,
+    /// Location to fill into the draft, if one was given
+    pub event_loc: Option<String>,
+    /// Discord id of the user who started the poll
+    pub creator_id: String,
+    /// Channel the poll was posted in
+    pub channel_id: String,
+    /// Message id of the poll
+    pub message_id: String,
+    /// When voting closes
+    pub closes_time: NaiveDateTime,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "polls"]
+pub struct NewPoll {
+    /// Name of the event being planned
+    pub event_name: String,
+    /// Description to fill into the draft, if one was given
+    pub event_desc: Option<String>,
+    /// Location to fill into the draft, if one was given
+    pub event_loc: Option<String>,
+    /// Discord id of the user who started the poll
+    pub creator_id: String,
+    /// Channel the poll was posted in
+    pub channel_id: String,
+    /// Message id of the poll
+    pub message_id: String,
+    /// When voting closes
+    pub closes_time: NaiveDateTime,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[table_name = "poll_options"]
+pub struct NewPollOption {
+    /// Poll ID
+    pub poll_id: i32,
+    /// Position of the option in the poll, starting at 0
+    pub position: i32,
+    /// Time being voted on
+    pub option_time: NaiveDateTime,
+}
+
+#[derive(Queryable, Insertable, Clone, Debug)]
+#[table_name = "guild_settings"]
+pub struct GuildSettings {
+    /// Discord guild id
+    pub guild_id: String,
+    /// Language to send messages in, `None` to use the configured language
+    pub language: Option<String>,
+    /// How reminders are sent, `None` to use the configured mode
+    pub reminder_mode: Option<String>,
+    /// Channel id reminders are posted in, `None` to use the configured channel
+    pub reminder_channel: Option<String>,
+    /// Whether events that overlap another event can not be created
+    pub block_conflicts: bool,
+    /// Message id of the pinned weekly digest, kept up to date instead of posting a new one
+    pub digest_message_id: Option<String>,
+    /// Message id of the pinned calendar in the event channel
+    pub calendar_message_id: Option<String>,
+}
+
+impl GuildSettings {
+    /// Settings of a guild that has not changed anything
+    pub fn new(guild_id: String) -> Self {
+        GuildSettings {
+            guild_id,
+            language: None,
+            reminder_mode: None,
+            reminder_channel: None,
+            block_conflicts: false,
+            digest_message_id: None,
+            calendar_message_id: None,
+        }
+    }
+}
+
+#[derive(Queryable, Insertable, Clone, Debug)]
+#[table_name = "user_preferences"]
+pub struct UserPreferences {
+    /// Discord user id
+    pub user_id: String,
+    /// Timezone to show times in, `None` to use the configured timezone
+    pub timezone: Option<String>,
+    /// Receive a DM when reacting to an event
+    pub confirmation_dms: bool,
+    /// Receive a DM when an event is about to start
+    pub reminder_dms: bool,
+    /// Receive DMs from the bot at all
+    pub dms: bool,
+    /// Receive a survey after events with one
+    pub survey_dms: bool,
+    /// Receive the weekly digest of upcoming events as a DM
+    pub digest_dms: bool,
+}
+
+impl UserPreferences {
+    /// Preferences of a user who has not changed anything
+    pub fn new(user_id: String) -> Self {
+        UserPreferences {
+            user_id,
+            timezone: None,
+            confirmation_dms: true,
+            reminder_dms: true,
+            dms: true,
+            survey_dms: true,
+            digest_dms: false,
+        }
+    }
 }
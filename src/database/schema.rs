@@ -1,13 +1,184 @@
+table! {
+    attendance (id) {
+        id -> Integer,
+        event_id -> Integer,
+        event_name -> Text,
+        event_time -> Datetime,
+        user_id -> Varchar,
+        rsvp -> Bool,
+        attended -> Bool,
+    }
+}
+
+table! {
+    check_ins (id) {
+        id -> Integer,
+        event_id -> Integer,
+        user_id -> Varchar,
+    }
+}
+
+table! {
+    event_images (event_id) {
+        event_id -> Integer,
+        filename -> Varchar,
+        data -> Blob,
+        banner -> Bool,
+    }
+}
+
 table! {
     events (id) {
         id -> Integer,
-        event_name -> Varchar,
-        event_desc -> Varchar,
-        event_loc -> Varchar,
-        organizer -> Varchar,
+        event_name -> Text,
+        event_desc -> Text,
+        event_loc -> Text,
+        organizer -> Text,
         event_time -> Datetime,
         message_id -> Varchar,
-        thumbnail_link -> Varchar,
+        thumbnail_link -> Text,
         reminder_sent -> Integer,
+        creator_id -> Varchar,
+        publish_time -> Nullable<Datetime>,
+        interested_emoji -> Nullable<Varchar>,
+        uninterested_emoji -> Nullable<Varchar>,
+        reminder_mode -> Nullable<Varchar>,
+        attendee_role -> Bool,
+        role_id -> Nullable<Varchar>,
+        channels_lead -> Nullable<Integer>,
+        voice_channel_id -> Nullable<Varchar>,
+        text_channel_id -> Nullable<Varchar>,
+        discussion_link -> Nullable<Text>,
+        survey -> Bool,
+        tags -> Nullable<Text>,
+        image_link -> Nullable<Text>,
+    }
+}
+
+table! {
+    proposals (id) {
+        id -> Integer,
+        event_name -> Text,
+        event_desc -> Text,
+        event_loc -> Text,
+        organizer -> Text,
+        event_time -> Datetime,
+        thumbnail_link -> Text,
+        proposer_id -> Varchar,
+        message_id -> Varchar,
+    }
+}
+
+table! {
+    co_organizers (id) {
+        id -> Integer,
+        event_id -> Integer,
+        user_id -> Varchar,
+    }
+}
+
+table! {
+    failed_dms (id) {
+        id -> Integer,
+        event_id -> Integer,
+        user_id -> Varchar,
+    }
+}
+
+table! {
+    feedback (id) {
+        id -> Integer,
+        survey_id -> Integer,
+        user_id -> Varchar,
+        message_id -> Varchar,
+        rating -> Nullable<Integer>,
+        comment -> Nullable<Text>,
+    }
+}
+
+table! {
+    guild_settings (guild_id) {
+        guild_id -> Varchar,
+        language -> Nullable<Varchar>,
+        reminder_mode -> Nullable<Varchar>,
+        reminder_channel -> Nullable<Varchar>,
+        block_conflicts -> Bool,
+        digest_message_id -> Nullable<Varchar>,
+        calendar_message_id -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    past_events (id) {
+        id -> Integer,
+        event_name -> Text,
+        event_desc -> Text,
+        event_loc -> Text,
+        organizer -> Text,
+        event_time -> Datetime,
+        thumbnail_link -> Text,
+        creator_id -> Varchar,
+        interested_emoji -> Nullable<Varchar>,
+        uninterested_emoji -> Nullable<Varchar>,
+        reminder_mode -> Nullable<Varchar>,
+        attendee_role -> Bool,
+        channels_lead -> Nullable<Integer>,
+        survey -> Bool,
+        tags -> Nullable<Text>,
+        image_link -> Nullable<Text>,
+    }
+}
+
+table! {
+    poll_options (id) {
+        id -> Integer,
+        poll_id -> Integer,
+        position -> Integer,
+        option_time -> Datetime,
+    }
+}
+
+table! {
+    polls (id) {
+        id -> Integer,
+        event_name -> Text,
+        event_desc -> Nullable<Text>,
+        event_loc -> Nullable<Text>,
+        creator_id -> Varchar,
+        channel_id -> Varchar,
+        message_id -> Varchar,
+        closes_time -> Datetime,
+    }
+}
+
+table! {
+    subscriptions (id) {
+        id -> Integer,
+        user_id -> Varchar,
+        tag -> Varchar,
+        auto_rsvp -> Bool,
+    }
+}
+
+table! {
+    surveys (id) {
+        id -> Integer,
+        event_id -> Integer,
+        event_name -> Text,
+        creator_id -> Varchar,
+        sent_time -> Datetime,
+        co_organizers -> Nullable<Text>,
+    }
+}
+
+table! {
+    user_preferences (user_id) {
+        user_id -> Varchar,
+        timezone -> Nullable<Varchar>,
+        confirmation_dms -> Bool,
+        reminder_dms -> Bool,
+        dms -> Bool,
+        survey_dms -> Bool,
+        digest_dms -> Bool,
     }
 }
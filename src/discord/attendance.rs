@@ -0,0 +1,220 @@
+use super::delivery::get_reaction_users;
+use super::{
+    emoji_matches, get_config, get_messages, get_scheduler, interested_emoji, parse_reaction_type,
+    EVENT_DURATION_MINUTES,
+};
+use crate::database::models::{Event, NewAttendance};
+use crate::database::{
+    add_check_in, get_check_ins, get_event_by_id, get_event_by_msg_id, get_event_by_name,
+    get_user_attendance, insert_attendance,
+};
+use crate::hypebot_config::HypeBotConfig;
+use chrono::{DateTime, Duration, Utc};
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, Message, MessageId, Reaction};
+use serenity::prelude::{Context, RwLock, ShareMap};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use white_rabbit::DateResult;
+
+#[command]
+/// Checks you in to an event that is happening now
+///
+/// `~checkin "event name"`
+///
+/// **Note**
+/// You can also check in by reacting to the announcement once the event has started.
+fn checkin(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+    let event = match get_event_by_name(config.db_url.clone(), event_name) {
+        Ok(event) => event,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("event_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    if !is_check_in_open(&event) {
+        msg.reply(
+            &ctx,
+            messages.get("checkin_closed", &[("event", &event.event_name)]),
+        )?;
+        return Ok(());
+    }
+
+    add_check_in(config.db_url.clone(), event.id, msg.author.id.0.to_string())?;
+
+    msg.reply(
+        &ctx,
+        messages.get("checkin_done", &[("event", &event.event_name)]),
+    )?;
+
+    Ok(())
+}
+
+#[command]
+/// Shows how many events a user has RSVP'd to and attended
+///
+/// `~stats @user`
+///
+/// **Note**
+/// Shows your own stats if no user is mentioned. The no-show rate is the share of RSVPs the user
+/// did not check in to.
+fn stats(ctx: &mut Context, msg: &Message, _args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let user = msg.mentions.first().unwrap_or(&msg.author);
+
+    let records = get_user_attendance(config.db_url.clone(), user.id.0.to_string())?;
+    let rsvps = records.iter().filter(|(rsvp, _)| *rsvp).count();
+    let attended = records.iter().filter(|(_, attended)| *attended).count();
+    let no_shows = records
+        .iter()
+        .filter(|(rsvp, attended)| *rsvp && !*attended)
+        .count();
+
+    let no_show_rate = if rsvps > 0 {
+        format!("{:.0}%", no_shows as f64 * 100.0 / rsvps as f64)
+    } else {
+        messages.get("no_show_rate_none", &[])
+    };
+
+    msg.reply(
+        &ctx,
+        messages.get(
+            "attendance_stats",
+            &[
+                ("user", &user.tag()),
+                ("attended", &attended.to_string()),
+                ("rsvps", &rsvps.to_string()),
+                ("no_shows", &no_shows.to_string()),
+                ("no_show_rate", &no_show_rate),
+            ],
+        ),
+    )?;
+
+    Ok(())
+}
+
+/// Checks if an event has started and is not over yet
+fn is_check_in_open(event: &Event) -> bool {
+    let now = Utc::now().naive_utc();
+
+    event.publish_time.is_none()
+        && now >= event.event_time
+        && now <= event.event_time + Duration::minutes(EVENT_DURATION_MINUTES)
+}
+
+/// Schedules the check-in reaction to be added when an event starts
+pub fn schedule_check_in(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Event) {
+    let scheduler = get_scheduler(data).unwrap();
+    let mut scheduler = scheduler.write();
+    let start_time = DateTime::<Utc>::from_utc(event.event_time, Utc);
+    let http = http.clone();
+    let data = data.clone();
+    let event = event.clone();
+
+    scheduler.add_task_datetime(start_time, move |_| {
+        open_check_in(&http, &data, &event);
+        DateResult::Done
+    });
+}
+
+/// Adds the check-in reaction to the announcement of an event that has started
+fn open_check_in(http: &Http, data: &Arc<RwLock<ShareMap>>, event: &Event) {
+    let config = get_config(data).unwrap();
+
+    // Skip events that have been canceled or rescheduled since check-in was scheduled
+    match get_event_by_id(config.db_url.clone(), event.id) {
+        Ok(current_event) if current_event.event_time == event.event_time => {}
+        _ => return,
+    }
+
+    if let Ok(message_id) = event.message_id.parse::<u64>() {
+        http.create_reaction(
+            config.event_channel,
+            message_id,
+            &parse_reaction_type(&config.checkin_emoji),
+        )
+        .ok();
+    }
+}
+
+/// Checks in the user who reacted with the check-in emoji to an event that is happening now
+pub fn check_in_reaction(ctx: &Context, reaction: &Reaction) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+
+    if reaction.channel_id.0 != config.event_channel
+        || !emoji_matches(&reaction.emoji, &config.checkin_emoji)
+        || reaction.user_id == ctx.cache.read().user.id
+    {
+        return Ok(());
+    }
+
+    let event = match get_event_by_msg_id(config.db_url.clone(), reaction.message_id.0.to_string())
+    {
+        Ok(event) => event,
+        Err(_) => return Ok(()),
+    };
+
+    if is_check_in_open(&event) {
+        add_check_in(
+            config.db_url.clone(),
+            event.id,
+            reaction.user_id.0.to_string(),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Records who was interested in an event and who checked in, before the event is removed
+///
+/// Returns the records so they can be used to follow up with attendees.
+pub fn record_attendance(http: &Http, config: &HypeBotConfig, event: &Event) -> Vec<NewAttendance> {
+    let message_id = match event.message_id.parse::<u64>() {
+        Ok(message_id) => MessageId(message_id),
+        Err(_) => return Vec::new(),
+    };
+
+    let interested: BTreeSet<String> = get_reaction_users(
+        http,
+        ChannelId(config.event_channel),
+        message_id,
+        parse_reaction_type(&interested_emoji(config, &event.interested_emoji)),
+    )
+    .iter()
+    .map(|user| user.id.0.to_string())
+    .collect();
+    let checked_in: BTreeSet<String> = get_check_ins(config.db_url.clone(), event.id)
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    let records: Vec<NewAttendance> = interested
+        .union(&checked_in)
+        .map(|user_id| NewAttendance {
+            event_id: event.id,
+            event_name: event.event_name.clone(),
+            event_time: event.event_time,
+            user_id: user_id.clone(),
+            rsvp: interested.contains(user_id),
+            attended: checked_in.contains(user_id),
+        })
+        .collect();
+
+    if !records.is_empty() {
+        if let Err(e) = insert_attendance(config.db_url.clone(), &records) {
+            error!(
+                "Unable to record attendance for {}: {}",
+                event.event_name, e
+            );
+        }
+    }
+
+    records
+}
@@ -0,0 +1,122 @@
+use super::discussion::message_link;
+use super::time::discord_timestamp;
+use super::{event_guild_id, get_config, get_messages, EVENT_DURATION_MINUTES};
+use crate::database::models::Event;
+use crate::database::{get_all_events, get_guild_settings, set_guild_calendar_message};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use chrono::{Duration, Utc};
+use serenity::builder::CreateEmbed;
+use serenity::framework::standard::CommandError;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, GuildId, MessageId};
+use serenity::prelude::{RwLock, ShareMap};
+use serenity::utils::Colour;
+use std::sync::Arc;
+
+/// Longest embed description Discord allows
+const MAX_CALENDAR_LEN: usize = 2048;
+
+/// Updates the pinned calendar of upcoming events in the event channel, if it is enabled
+///
+/// The calendar is posted and pinned the first time, or again if it was deleted.
+pub fn update_calendar(http: &Http, data: &Arc<RwLock<ShareMap>>) {
+    let config = match get_config(data) {
+        Ok(config) if config.calendar => config,
+        _ => return,
+    };
+
+    if let Err(e) = edit_calendar(http, data, &config) {
+        error!("Unable to update the calendar: {}", e.0);
+    }
+}
+
+/// Edits the calendar message, posting and pinning a new one if there is none
+fn edit_calendar(
+    http: &Http,
+    data: &Arc<RwLock<ShareMap>>,
+    config: &HypeBotConfig,
+) -> Result<(), CommandError> {
+    let guild_id = event_guild_id(http, config)
+        .ok_or(CommandError("Unable to find the event guild".to_string()))?;
+    let messages = get_messages(data, Some(guild_id))?;
+    let channel_id = ChannelId(config.event_channel);
+
+    // Events stay on the calendar until they are over and cleaned up
+    let started_after = Utc::now().naive_utc() - Duration::minutes(EVENT_DURATION_MINUTES);
+    let events: Vec<Event> = get_all_events(config.db_url.clone())?
+        .into_iter()
+        .filter(|event| event.publish_time.is_none() && event.event_time > started_after)
+        .collect();
+
+    let calendar_msg = get_guild_settings(config.db_url.clone(), guild_id.0.to_string())
+        .ok()
+        .and_then(|settings| settings.calendar_message_id)
+        .and_then(|message_id| message_id.parse::<u64>().ok());
+
+    if let Some(message_id) = calendar_msg {
+        let edited = channel_id.edit_message(http, MessageId(message_id), |m| {
+            m.embed(|e| build_calendar_embed(e, &messages, guild_id, channel_id, &events))
+        });
+
+        if edited.is_ok() {
+            return Ok(());
+        }
+    }
+
+    let calendar_msg = channel_id.send_message(http, |m| {
+        m.embed(|e| build_calendar_embed(e, &messages, guild_id, channel_id, &events))
+    })?;
+    calendar_msg.pin(http)?;
+
+    set_guild_calendar_message(
+        config.db_url.clone(),
+        guild_id.0.to_string(),
+        Some(calendar_msg.id.0.to_string()),
+    )?;
+
+    Ok(())
+}
+
+/// Builds the calendar embed, listing events in the order they happen with a countdown to each
+fn build_calendar_embed<'a>(
+    e: &'a mut CreateEmbed,
+    messages: &Messages,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    events: &[Event],
+) -> &'a mut CreateEmbed {
+    let mut description = String::new();
+
+    for event in events {
+        let link = match event.message_id.parse::<u64>() {
+            Ok(message_id) => message_link(guild_id, channel_id, MessageId(message_id)),
+            Err(_) => continue,
+        };
+
+        let line = messages.get(
+            "calendar_event",
+            &[
+                ("event", &event.event_name),
+                ("link", &link),
+                ("timestamp", &discord_timestamp(event.event_time, 'F')),
+                ("relative", &discord_timestamp(event.event_time, 'R')),
+            ],
+        );
+
+        // Leave out the latest events if they do not fit in the embed
+        if description.len() + line.len() + 1 > MAX_CALENDAR_LEN {
+            break;
+        }
+        description.push_str(&line);
+        description.push('\n');
+    }
+
+    if description.is_empty() {
+        description = messages.get("calendar_empty", &[]);
+    }
+
+    e.title(messages.get("calendar_title", &[]))
+        .color(Colour::PURPLE)
+        .description(description)
+}
@@ -0,0 +1,166 @@
+use super::{edit_event_msg, event_guild_id, get_config, get_messages, get_scheduler};
+use crate::database::models::Event;
+use crate::database::{get_event_by_id, set_event_channels};
+use crate::hypebot_config::HypeBotConfig;
+use chrono::{DateTime, Utc};
+use serenity::framework::standard::CommandError;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, ChannelType, GuildId};
+use serenity::prelude::{RwLock, ShareMap};
+use std::sync::Arc;
+use white_rabbit::DateResult;
+
+/// Longest channel name Discord allows
+const MAX_CHANNEL_NAME_LEN: usize = 100;
+
+/// Parses the `channels` option, `yes` for when the event is posted or a number of minutes before it
+pub fn parse_channels_lead(value: &str) -> Option<Option<i32>> {
+    match value.to_lowercase().as_str() {
+        "yes" | "true" | "on" => Some(Some(0)),
+        "no" | "false" | "off" => Some(None),
+        minutes => minutes
+            .parse::<i32>()
+            .ok()
+            .filter(|minutes| *minutes > 0)
+            .map(Some),
+    }
+}
+
+/// Schedules the voice and text channels of an event to be created, if it wants them
+pub fn schedule_event_channels(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Event) {
+    let lead = match event.channels_lead {
+        Some(lead) if event.voice_channel_id.is_none() && event.text_channel_id.is_none() => lead,
+        _ => return,
+    };
+
+    let create_time = if lead == 0 {
+        Utc::now()
+    } else {
+        DateTime::<Utc>::from_utc(event.event_time, Utc) - chrono::Duration::minutes(lead.into())
+    };
+
+    let scheduler = get_scheduler(data).unwrap();
+    let mut scheduler = scheduler.write();
+    let http = http.clone();
+    let data = data.clone();
+    let event = event.clone();
+
+    scheduler.add_task_datetime(create_time, move |_| {
+        if let Err(e) = create_event_channels(&http, &data, &event) {
+            error!(
+                "Unable to create channels for '{}': {}",
+                event.event_name, e.0
+            );
+        }
+        DateResult::Done
+    });
+}
+
+/// Creates the voice and text channels of an event and links them in the announcement
+fn create_event_channels(
+    http: &Arc<Http>,
+    data: &Arc<RwLock<ShareMap>>,
+    event: &Event,
+) -> Result<(), CommandError> {
+    let config = get_config(data)?;
+
+    // Skip events that have been canceled, rescheduled, or already have channels
+    let event = match get_event_by_id(config.db_url.clone(), event.id) {
+        Ok(current_event)
+            if current_event.event_time == event.event_time
+                && current_event.voice_channel_id.is_none()
+                && current_event.text_channel_id.is_none() =>
+        {
+            current_event
+        }
+        _ => return Ok(()),
+    };
+
+    let guild_id = event_guild_id(http, &config)
+        .ok_or(CommandError("Unable to find the event guild".to_string()))?;
+    let name: String = event
+        .event_name
+        .chars()
+        .take(MAX_CHANNEL_NAME_LEN)
+        .collect();
+
+    // Channels that are not stored would never be cleaned up, so they are deleted on failure
+    let voice = create_channel(http, &config, guild_id, &name, ChannelType::Voice)?;
+    let text = match create_channel(http, &config, guild_id, &name, ChannelType::Text) {
+        Ok(text) => text,
+        Err(e) => {
+            voice.delete(http).ok();
+            return Err(e);
+        }
+    };
+
+    if let Err(e) = set_event_channels(
+        config.db_url.clone(),
+        event.id,
+        Some(voice.0.to_string()),
+        Some(text.0.to_string()),
+    ) {
+        voice.delete(http).ok();
+        text.delete(http).ok();
+        return Err(e.into());
+    }
+
+    let event = get_event_by_id(config.db_url.clone(), event.id)?;
+    let messages = get_messages(data, Some(guild_id))?;
+    edit_event_msg(http, &config, &messages, &event)?;
+
+    Ok(())
+}
+
+/// Creates a channel in the event category
+fn create_channel(
+    http: &Http,
+    config: &HypeBotConfig,
+    guild_id: GuildId,
+    name: &str,
+    kind: ChannelType,
+) -> Result<ChannelId, CommandError> {
+    let channel = guild_id.create_channel(http, |c| {
+        c.name(name).kind(kind);
+        if let Some(category) = config.event_category {
+            c.category(category);
+        }
+        c
+    })?;
+
+    Ok(channel.id)
+}
+
+/// Removes the channels of an event once it is over
+///
+/// The voice channel is deleted. The text channel is moved to the archive category if one is
+/// configured so the discussion is kept, otherwise it is deleted too.
+pub fn remove_event_channels(http: &Http, config: &HypeBotConfig, event: &Event) {
+    if let Some(voice) = parse_channel_id(&event.voice_channel_id) {
+        voice.delete(http).ok();
+    }
+
+    if let Some(text) = parse_channel_id(&event.text_channel_id) {
+        let result = match config.archive_category {
+            Some(archive_category) => text
+                .edit(http, |c| c.category(ChannelId(archive_category)))
+                .map(|_| ()),
+            None => text.delete(http).map(|_| ()),
+        };
+
+        if let Err(e) = result {
+            error!(
+                "Unable to remove text channel for {}: {}",
+                event.event_name, e
+            );
+        }
+    }
+}
+
+/// Parses a stored channel id
+pub fn parse_channel_id(channel_id: &Option<String>) -> Option<ChannelId> {
+    channel_id
+        .as_ref()
+        .and_then(|channel_id| channel_id.parse::<u64>().ok())
+        .map(ChannelId)
+}
@@ -0,0 +1,180 @@
+use super::delivery::queue_event_dm;
+use super::time::format_time;
+use super::{
+    get_config, get_messages, interested_emoji, parse_reaction_type, user_timezone, wants_dm,
+    Notification, EVENT_DURATION_MINUTES,
+};
+use crate::database::models::{Event, NewEvent};
+use crate::database::{get_event_by_msg_id, get_events_between, get_guild_settings};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use chrono::{Duration, NaiveDateTime};
+use serenity::framework::standard::CommandResult;
+use serenity::model::prelude::{ChannelId, GuildId, MessageId, Reaction, UserId};
+use serenity::prelude::Context;
+
+/// Most overlapping events checked for a user's reaction when they become interested in an event
+const MAX_CHECKED_CONFLICTS: usize = 5;
+
+/// Gets the stored events that overlap an event starting at a time
+///
+/// Every event is assumed to last `EVENT_DURATION_MINUTES`. Events waiting to be posted count too,
+/// `exclude` skips the event itself when it is being edited.
+pub fn find_conflicts(
+    config: &HypeBotConfig,
+    event_time: NaiveDateTime,
+    exclude: Option<i32>,
+) -> Vec<Event> {
+    let duration = Duration::minutes(EVENT_DURATION_MINUTES);
+
+    get_events_between(
+        config.db_url.clone(),
+        event_time - duration,
+        event_time + duration,
+    )
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|event| Some(event.id) != exclude)
+    .collect()
+}
+
+/// Builds a warning listing the events an event overlaps, `None` if there are none
+///
+/// Events at the same location are called out, since they clash for more than just attendees.
+pub fn conflict_warning(
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &NewEvent,
+    conflicts: &[Event],
+) -> Option<String> {
+    if conflicts.is_empty() {
+        return None;
+    }
+
+    let lines: Vec<String> = conflicts
+        .iter()
+        .map(|conflict| {
+            let time = format_time(messages, &config.event_timezone, conflict.event_time);
+            if same_location(&conflict.event_loc, &event.event_loc) {
+                messages.get(
+                    "conflict_event_same_place",
+                    &[
+                        ("event", &conflict.event_name),
+                        ("time", &time),
+                        ("location", &conflict.event_loc),
+                    ],
+                )
+            } else {
+                messages.get(
+                    "conflict_event",
+                    &[("event", &conflict.event_name), ("time", &time)],
+                )
+            }
+        })
+        .collect();
+
+    let heading = if conflicts
+        .iter()
+        .any(|conflict| same_location(&conflict.event_loc, &event.event_loc))
+    {
+        messages.get("conflict_same_place", &[])
+    } else {
+        messages.get("conflict_overlap", &[])
+    };
+
+    Some(format!("{}\n{}", heading, lines.join("\n")))
+}
+
+/// Checks if a guild blocks events that overlap another event
+pub fn blocks_conflicts(config: &HypeBotConfig, guild_id: Option<GuildId>) -> bool {
+    guild_id
+        .and_then(|guild_id| get_guild_settings(config.db_url.clone(), guild_id.0.to_string()).ok())
+        .map(|settings| settings.block_conflicts)
+        .unwrap_or(false)
+}
+
+/// Checks if two locations are the same, ignoring case and surrounding whitespace
+fn same_location(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// DMs a user who became interested in an event about other events they are interested in that
+/// overlap it
+///
+/// Only the first `MAX_CHECKED_CONFLICTS` overlapping events are checked, each with one request.
+pub fn warn_rsvp_conflicts(ctx: &Context, reaction: &Reaction) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+
+    let event = match get_event_by_msg_id(config.db_url.clone(), reaction.message_id.0.to_string())
+    {
+        Ok(event) => event,
+        Err(_) => return Ok(()),
+    };
+
+    let overlapping: Vec<Event> = find_conflicts(&config, event.event_time, Some(event.id))
+        .into_iter()
+        .filter(|other| other.publish_time.is_none())
+        .take(MAX_CHECKED_CONFLICTS)
+        .collect();
+
+    if overlapping.is_empty() {
+        return Ok(());
+    }
+
+    let user = reaction.user(&ctx.http)?;
+    if !wants_dm(&config, &user, Notification::Confirmation) {
+        return Ok(());
+    }
+
+    let clashes: Vec<Event> = overlapping
+        .into_iter()
+        .filter(|other| is_interested(ctx, &config, other, reaction.user_id))
+        .collect();
+
+    let messages = get_messages(&ctx.data, reaction.guild_id)?;
+    let tz = user_timezone(&config, &user);
+
+    for other in clashes {
+        let msg = messages.get(
+            "rsvp_conflict",
+            &[
+                ("event", &event.event_name),
+                ("other", &other.event_name),
+                ("time", &format_time(&messages, &tz, other.event_time)),
+            ],
+        );
+        queue_event_dm(
+            &ctx.data,
+            ctx.http.clone(),
+            config.clone(),
+            messages.clone(),
+            event.clone(),
+            user.clone(),
+            msg,
+        );
+    }
+
+    Ok(())
+}
+
+/// Checks if a user reacted as interested to an event
+///
+/// Reaction users are listed in order of id, so asking for the first one after the id just below
+/// the user's finds them with a single request instead of listing everyone.
+fn is_interested(ctx: &Context, config: &HypeBotConfig, event: &Event, user_id: UserId) -> bool {
+    let message_id = match event.message_id.parse::<u64>() {
+        Ok(message_id) => MessageId(message_id),
+        Err(_) => return false,
+    };
+
+    ChannelId(config.event_channel)
+        .reaction_users(
+            &ctx.http,
+            message_id,
+            parse_reaction_type(&interested_emoji(config, &event.interested_emoji)),
+            Some(1),
+            Some(UserId(user_id.0 - 1)),
+        )
+        .map(|users| users.first().map(|user| user.id) == Some(user_id))
+        .unwrap_or(false)
+}
@@ -0,0 +1,378 @@
+use super::time::format_time;
+use super::{record_event_dm_failure, user_timezone, wants_dm, Notification};
+use crate::database::models::Event;
+use crate::database::{clear_failed_dm, get_event_by_id, remove_failed_dms};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use serenity::http::{Http, HttpError};
+use serenity::model::prelude::{ChannelId, MessageId, ReactionType, User, UserId};
+use serenity::prelude::{Mutex, RwLock, ShareMap, TypeMapKey};
+use serenity::Error;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Most users Discord returns per page of reactions
+const REACTION_PAGE_SIZE: u8 = 100;
+/// Time to wait between DMs, to stay well under Discord's rate limits
+pub const DELIVERY_INTERVAL: Duration = Duration::from_millis(500);
+/// Times to try sending a DM before giving up
+const MAX_ATTEMPTS: u32 = 3;
+/// Organizers are sent progress reports for deliveries with at least this many users
+const PROGRESS_INTERVAL: usize = 50;
+
+/// A batch of DMs waiting to be sent
+type Delivery = Box<dyn FnOnce() + Send>;
+
+/// Queue of DM batches, sent one after another by a single thread so every DM the bot sends shares
+/// the same pacing
+pub struct DeliveryQueue;
+
+impl TypeMapKey for DeliveryQueue {
+    type Value = Mutex<Sender<Delivery>>;
+}
+
+/// Starts the thread that sends queued DMs
+///
+/// A batch that panics is dropped, the thread carries on with the next one so later batches are
+/// still sent.
+pub fn start_delivery_queue(data: &Arc<RwLock<ShareMap>>) {
+    let (sender, receiver) = channel::<Delivery>();
+
+    thread::spawn(move || {
+        for delivery in receiver {
+            if catch_unwind(AssertUnwindSafe(delivery)).is_err() {
+                error!("A batch of DMs panicked, moving on to the next one");
+            }
+        }
+    });
+
+    data.write().insert::<DeliveryQueue>(Mutex::new(sender));
+}
+
+/// Queues a batch of DMs, which should wait `DELIVERY_INTERVAL` after each DM it sends
+pub fn queue_delivery<F>(data: &Arc<RwLock<ShareMap>>, delivery: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    let data = data.read();
+    let queue = match data.get::<DeliveryQueue>() {
+        Some(queue) => queue,
+        None => {
+            error!("Unable to find the delivery queue");
+            return;
+        }
+    };
+
+    if queue.lock().send(Box::new(delivery)).is_err() {
+        error!("The delivery queue has stopped");
+    }
+}
+
+/// Queues a single DM, sent with the same pacing and retries as every other DM
+pub fn queue_dm(data: &Arc<RwLock<ShareMap>>, http: Arc<Http>, user: User, message: String) {
+    queue_delivery(data, move || {
+        send_with_retries(&http, &user, &message);
+        thread::sleep(DELIVERY_INTERVAL);
+    });
+}
+
+/// Queues a single DM about an event
+///
+/// If it can not be delivered, the failure is recorded for the `unreachable` command and the user
+/// is pinged in the fallback channel, once per event.
+pub fn queue_event_dm(
+    data: &Arc<RwLock<ShareMap>>,
+    http: Arc<Http>,
+    config: HypeBotConfig,
+    messages: Messages,
+    event: Event,
+    user: User,
+    message: String,
+) {
+    queue_delivery(data, move || {
+        if send_with_retries(&http, &user, &message) {
+            clear_failed_dm(config.db_url.clone(), event.id, user.id.0.to_string()).ok();
+        } else {
+            record_event_dm_failure(&http, &config, &messages, &event, &user);
+        }
+
+        thread::sleep(DELIVERY_INTERVAL);
+    });
+}
+
+/// Gets every user who reacted to a message with an emoji, one page at a time
+///
+/// Bots, including HypeBot's own reaction, are left out.
+pub fn get_reaction_users(
+    http: &Http,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    reaction_type: ReactionType,
+) -> Vec<User> {
+    let mut users = Vec::new();
+    let mut after: Option<UserId> = None;
+
+    loop {
+        let page = match channel_id.reaction_users(
+            http,
+            message_id,
+            reaction_type.clone(),
+            Some(REACTION_PAGE_SIZE),
+            after,
+        ) {
+            Ok(page) => page,
+            Err(e) => {
+                error!("Unable to get reaction users: {}", e);
+                break;
+            }
+        };
+
+        let page_len = page.len();
+        let last = page.last().map(|user| user.id);
+        users.extend(page.into_iter().filter(|user| !user.bot));
+
+        after = match next_page_after(page_len, last) {
+            Some(last) => Some(last),
+            None => break,
+        };
+    }
+
+    users
+}
+
+/// Gets the user to fetch the next page of reactions after, `None` once the last page is reached
+///
+/// Discord returns full pages until it runs out of users, so a page with room left is the last.
+fn next_page_after(page_len: usize, last: Option<UserId>) -> Option<UserId> {
+    if page_len < REACTION_PAGE_SIZE as usize {
+        None
+    } else {
+        last
+    }
+}
+
+/// DMs about an event to send to a group of users
+pub struct EventDms {
+    pub config: HypeBotConfig,
+    pub messages: Messages,
+    pub event: Event,
+    pub users: Vec<User>,
+    /// Catalog key of the message, filled in with the event and its time in each user's timezone
+    pub msg_key: &'static str,
+    /// Users who have turned off this kind of notification are skipped
+    pub notification: Notification,
+}
+
+/// Queues a DM to every user about an event, without blocking the caller
+///
+/// Each user gets the `msg_key` message with the event time in their own timezone, unless they
+/// have turned off this kind of notification. DMs are spaced out to respect rate limits and
+/// retried when Discord has a temporary problem. The organizer is kept up to date on large
+/// deliveries.
+pub fn deliver_event_dms(data: &Arc<RwLock<ShareMap>>, http: Arc<Http>, dms: EventDms) {
+    let EventDms {
+        config,
+        messages,
+        event,
+        users,
+        msg_key,
+        notification,
+    } = dms;
+
+    queue_delivery(data, move || {
+        let total = users.len();
+        let mut delivered = 0;
+        let mut failed = 0;
+        let mut skipped = 0;
+
+        for (i, user) in users.into_iter().enumerate() {
+            if !wants_dm(&config, &user, notification) {
+                skipped += 1;
+                continue;
+            }
+
+            let tz = user_timezone(&config, &user);
+            let msg = messages.get(
+                msg_key,
+                &[
+                    ("event", &event.event_name),
+                    ("time", &format_time(&messages, &tz, event.event_time)),
+                ],
+            );
+
+            if send_with_retries(&http, &user, &msg) {
+                clear_failed_dm(config.db_url.clone(), event.id, user.id.0.to_string()).ok();
+                delivered += 1;
+            } else {
+                record_event_dm_failure(&http, &config, &messages, &event, &user);
+                failed += 1;
+            }
+
+            if total >= PROGRESS_INTERVAL && (i + 1) % PROGRESS_INTERVAL == 0 && i + 1 < total {
+                let sent = (i + 1).to_string();
+                let total = total.to_string();
+                report_to_organizer(
+                    &http,
+                    &config,
+                    &event,
+                    &messages.get(
+                        "delivery_progress",
+                        &[
+                            ("event", &event.event_name),
+                            ("sent", &sent),
+                            ("total", &total),
+                        ],
+                    ),
+                );
+            }
+
+            thread::sleep(DELIVERY_INTERVAL);
+        }
+
+        // The event may have been removed while sending, like when it is canceled
+        if get_event_by_id(config.db_url.clone(), event.id).is_err() {
+            remove_failed_dms(config.db_url.clone(), event.id).ok();
+        }
+
+        info!(
+            "Delivered {} of {} DMs about {}, {} failed and {} skipped",
+            delivered, total, event.event_name, failed, skipped
+        );
+
+        if total >= PROGRESS_INTERVAL || failed > 0 {
+            let delivered = delivered.to_string();
+            let failed = failed.to_string();
+            let skipped = skipped.to_string();
+            report_to_organizer(
+                &http,
+                &config,
+                &event,
+                &messages.get(
+                    "delivery_done",
+                    &[
+                        ("event", &event.event_name),
+                        ("delivered", &delivered),
+                        ("failed", &failed),
+                        ("skipped", &skipped),
+                    ],
+                ),
+            );
+        }
+    });
+}
+
+/// Sends a DM, retrying with a growing delay if Discord has a temporary problem
+fn send_with_retries(http: &Http, user: &User, message: &str) -> bool {
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = user
+            .create_dm_channel(http)
+            .and_then(|dm_channel| dm_channel.send_message(http, |m| m.content(message)));
+
+        match result {
+            Ok(_) => return true,
+            Err(ref e) if !is_transient(e) => return false,
+            Err(e) => {
+                warn!("Attempt {} to DM {} failed: {}", attempt, user.tag(), e);
+                thread::sleep(DELIVERY_INTERVAL * 2u32.pow(attempt));
+            }
+        }
+    }
+
+    false
+}
+
+/// Checks if a failed request is worth retrying
+///
+/// Only being rate limited, server errors, and connection problems can go away on their own.
+/// Anything else, like a user having DMs closed, will fail again.
+fn is_transient(error: &Error) -> bool {
+    match error {
+        Error::Http(http_error) => match http_error.as_ref() {
+            HttpError::UnsuccessfulRequest(response) => {
+                response.status_code.is_server_error() || response.status_code.as_u16() == 429
+            }
+            HttpError::Request(_) => true,
+            _ => false,
+        },
+        Error::Io(_) => true,
+        _ => false,
+    }
+}
+
+/// DMs the organizer of an event about a delivery, from within the batch being reported on
+fn report_to_organizer(http: &Http, config: &HypeBotConfig, event: &Event, message: &str) {
+    if let Ok(organizer_id) = event.creator_id.parse::<u64>() {
+        if let Ok(organizer) = http.get_user(organizer_id) {
+            if wants_dm(config, &organizer, Notification::Update) {
+                send_with_retries(http, &organizer, message);
+                thread::sleep(DELIVERY_INTERVAL);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serenity::http::error::{DiscordJsonError, ErrorResponse};
+    use serenity::http::StatusCode;
+    use std::io;
+
+    fn unsuccessful(status: u16) -> Error {
+        let error: DiscordJsonError =
+            serde_json::from_str(r#"{"code": 0, "message": "error"}"#).unwrap();
+
+        HttpError::UnsuccessfulRequest(ErrorResponse {
+            status_code: StatusCode::from_u16(status).unwrap(),
+            url: "https://discord.com/api/v6/channels/1/messages"
+                .parse()
+                .unwrap(),
+            error,
+        })
+        .into()
+    }
+
+    #[test]
+    fn is_transient_retries_rate_limits_and_server_errors() {
+        assert!(is_transient(&unsuccessful(429)));
+        assert!(is_transient(&unsuccessful(500)));
+        assert!(is_transient(&unsuccessful(502)));
+    }
+
+    #[test]
+    fn is_transient_gives_up_on_client_errors() {
+        // Discord answers 403 to DMs for users who do not accept them
+        assert!(!is_transient(&unsuccessful(403)));
+        assert!(!is_transient(&unsuccessful(404)));
+        assert!(!is_transient(&unsuccessful(400)));
+    }
+
+    #[test]
+    fn is_transient_retries_connection_problems() {
+        assert!(is_transient(&Error::Io(io::Error::new(
+            io::ErrorKind::ConnectionReset,
+            "reset"
+        ))));
+        assert!(!is_transient(&Error::Other("not a request error")));
+    }
+
+    #[test]
+    fn next_page_after_continues_after_a_full_page() {
+        let last = Some(UserId(42));
+
+        assert_eq!(
+            next_page_after(REACTION_PAGE_SIZE as usize, last),
+            Some(UserId(42))
+        );
+    }
+
+    #[test]
+    fn next_page_after_stops_after_a_partial_page() {
+        assert_eq!(next_page_after(99, Some(UserId(42))), None);
+        assert_eq!(next_page_after(1, Some(UserId(42))), None);
+        assert_eq!(next_page_after(0, None), None);
+    }
+}
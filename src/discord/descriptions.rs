@@ -0,0 +1,294 @@
+use crate::database::models::NewEvent;
+use crate::messages::Messages;
+use serenity::framework::standard::{Args, Delimiter};
+use serenity::model::prelude::Message;
+
+/// Longest embed title Discord allows
+pub const MAX_TITLE_LEN: usize = 256;
+/// Longest embed description Discord allows
+pub const MAX_DESCRIPTION_LEN: usize = 2048;
+/// Longest embed field value Discord allows
+pub const MAX_FIELD_LEN: usize = 1024;
+/// Longest embed footer Discord allows
+const MAX_FOOTER_LEN: usize = 2048;
+/// Most text Discord allows in an embed, counting its title, description, fields and footer
+const MAX_EMBED_LEN: usize = 6000;
+/// Largest description file that can be attached
+const MAX_DESCRIPTION_FILE_SIZE: u64 = 16 * 1024;
+/// Marks the end of shortened text
+const ELLIPSIS: char = '…';
+
+/// Splits a code block out of a command's arguments, for descriptions spanning multiple lines
+///
+/// The block is taken as is, so it can contain quotes and line breaks. Returns the remaining
+/// arguments and the contents of the block.
+pub fn take_code_block(args: &Args) -> (Args, Option<String>) {
+    let message = args.message();
+
+    let block = message.find("```").and_then(|start| {
+        message[start + 3..]
+            .find("```")
+            .map(|len| (start, start + 3 + len + 3))
+    });
+
+    match block {
+        Some((start, end)) => {
+            let text = message[start + 3..end - 3].trim().to_string();
+            let remaining = format!("{} {}", &message[..start], &message[end..]);
+
+            (
+                Args::new(remaining.trim(), &[Delimiter::Single(' ')]),
+                Some(text).filter(|text| !text.is_empty()),
+            )
+        }
+        None => (Args::new(message, &[Delimiter::Single(' ')]), None),
+    }
+}
+
+/// Why an attached description file can not be used
+#[derive(Debug, PartialEq)]
+pub enum DescriptionError {
+    TooLarge,
+    Download,
+    NotText,
+}
+
+impl DescriptionError {
+    /// Describes the error to reply with
+    pub fn describe(&self, messages: &Messages) -> String {
+        match self {
+            DescriptionError::TooLarge => messages.get(
+                "description_file_too_large",
+                &[("size", &(MAX_DESCRIPTION_FILE_SIZE / 1024).to_string())],
+            ),
+            DescriptionError::Download => messages.get("description_file_download", &[]),
+            DescriptionError::NotText => messages.get("description_file_not_text", &[]),
+        }
+    }
+}
+
+/// Reads a `.txt` file attached to a message as a description, `Ok(None)` if there is none
+pub fn attached_description(msg: &Message) -> Result<Option<String>, DescriptionError> {
+    let attachment = match msg
+        .attachments
+        .iter()
+        .find(|attachment| is_text_file(&attachment.filename))
+    {
+        Some(attachment) => attachment,
+        None => return Ok(None),
+    };
+
+    if attachment.size > MAX_DESCRIPTION_FILE_SIZE {
+        return Err(DescriptionError::TooLarge);
+    }
+
+    let data = attachment
+        .download()
+        .map_err(|_| DescriptionError::Download)?;
+
+    match String::from_utf8(data) {
+        Ok(text) => Ok(Some(text.trim().to_string())),
+        Err(_) => Err(DescriptionError::NotText),
+    }
+}
+
+/// Checks if an attachment is a text file, which is read as a description rather than an image
+pub fn is_text_file(filename: &str) -> bool {
+    filename.to_lowercase().ends_with(".txt")
+}
+
+/// Describes the first part of an event that does not fit in an announcement, `None` if it all fits
+pub fn length_error(messages: &Messages, event: &NewEvent) -> Option<String> {
+    let limits = [
+        ("name_too_long", &event.event_name, MAX_TITLE_LEN),
+        (
+            "description_too_long",
+            &event.event_desc,
+            MAX_DESCRIPTION_LEN,
+        ),
+        ("location_too_long", &event.event_loc, MAX_FIELD_LEN),
+        ("organizer_too_long", &event.organizer, MAX_FIELD_LEN),
+    ];
+
+    limits.iter().find_map(|(key, text, max)| {
+        let len = text.chars().count();
+        if len > *max {
+            Some(messages.get(key, &[("len", &len.to_string()), ("max", &max.to_string())]))
+        } else {
+            None
+        }
+    })
+}
+
+/// Shortens text to fit a Discord limit, ending it with an ellipsis if anything was cut
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+
+    let mut truncated: String = text.chars().take(max - 1).collect();
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+/// The text shown in an event's announcement embed
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbedText {
+    pub title: String,
+    pub description: String,
+    pub footer: String,
+    /// The part of the event each field shows, its name, its value, and whether it is inline
+    pub fields: Vec<(&'static str, String, String, bool)>,
+}
+
+impl EmbedText {
+    /// Shortens the text to fit Discord's limits
+    ///
+    /// The description takes whatever room the rest of the embed leaves under `MAX_EMBED_LEN`, so
+    /// it can be cut even when it is within `MAX_DESCRIPTION_LEN` on its own.
+    pub fn fit(&self) -> EmbedText {
+        let title = truncate(&self.title, MAX_TITLE_LEN);
+        let footer = truncate(&self.footer, MAX_FOOTER_LEN);
+        let fields: Vec<(&'static str, String, String, bool)> = self
+            .fields
+            .iter()
+            .map(|(part, name, value, inline)| {
+                (
+                    *part,
+                    truncate(name, MAX_TITLE_LEN),
+                    truncate(value, MAX_FIELD_LEN),
+                    *inline,
+                )
+            })
+            .collect();
+
+        let used = title.chars().count()
+            + footer.chars().count()
+            + fields
+                .iter()
+                .map(|(_, name, value, _)| name.chars().count() + value.chars().count())
+                .sum::<usize>();
+        let description_len = MAX_EMBED_LEN.saturating_sub(used).min(MAX_DESCRIPTION_LEN);
+
+        EmbedText {
+            title,
+            description: truncate(&self.description, description_len),
+            footer,
+            fields,
+        }
+    }
+
+    /// Lists the parts of the event that `fit` shortens
+    pub fn truncated_parts(&self) -> Vec<&'static str> {
+        let fitted = self.fit();
+        let mut parts = Vec::new();
+
+        if fitted.title != self.title {
+            parts.push("name");
+        }
+        if fitted.description != self.description {
+            parts.push("description");
+        }
+        for (field, fitted_field) in self.fields.iter().zip(fitted.fields.iter()) {
+            if field.2 != fitted_field.2 {
+                parts.push(field.0);
+            }
+        }
+
+        parts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embed_text(description_len: usize, location_len: usize) -> EmbedText {
+        EmbedText {
+            title: "Game Night".to_string(),
+            description: "d".repeat(description_len),
+            footer: "Local Event Time".to_string(),
+            fields: vec![
+                (
+                    "location",
+                    "Location".to_string(),
+                    "l".repeat(location_len),
+                    true,
+                ),
+                (
+                    "organizer",
+                    "Organizer".to_string(),
+                    "o".repeat(MAX_FIELD_LEN),
+                    true,
+                ),
+                ("tags", "Tags".to_string(), "t".repeat(MAX_FIELD_LEN), false),
+                (
+                    "discussion",
+                    "Discussion".to_string(),
+                    "d".repeat(MAX_FIELD_LEN),
+                    false,
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn truncate_keeps_short_text() {
+        assert_eq!(truncate("Game Night", 10), "Game Night");
+        assert_eq!(truncate("", 0), "");
+    }
+
+    #[test]
+    fn truncate_cuts_long_text_with_an_ellipsis() {
+        assert_eq!(truncate("Game Night", 5), "Game…");
+        assert_eq!(truncate("Game Night", 1), "…");
+        assert_eq!(truncate("Game Night", 0), "");
+    }
+
+    #[test]
+    fn truncate_counts_characters_not_bytes() {
+        assert_eq!(truncate("Noche de juegos ñ", 17), "Noche de juegos ñ");
+        assert_eq!(truncate("ñññññ", 3), "ññ…");
+        assert_eq!(truncate("ñññññ", 3).chars().count(), 3);
+    }
+
+    #[test]
+    fn fitting_text_is_not_truncated() {
+        let text = embed_text(100, 100);
+        assert_eq!(text.fit(), text);
+        assert!(text.truncated_parts().is_empty());
+    }
+
+    #[test]
+    fn long_parts_are_truncated_to_their_limits() {
+        let mut text = embed_text(100, MAX_FIELD_LEN + 10);
+        text.title = "n".repeat(MAX_TITLE_LEN + 1);
+        let fitted = text.fit();
+
+        assert_eq!(fitted.title.chars().count(), MAX_TITLE_LEN);
+        assert_eq!(fitted.fields[0].2.chars().count(), MAX_FIELD_LEN);
+        assert_eq!(text.truncated_parts(), vec!["name", "location"]);
+    }
+
+    #[test]
+    fn description_shrinks_to_fit_the_whole_embed() {
+        // Within its own limit, but the fields leave less room than that
+        let text = embed_text(MAX_DESCRIPTION_LEN, MAX_FIELD_LEN);
+        let fitted = text.fit();
+        let total = fitted.title.chars().count()
+            + fitted.description.chars().count()
+            + fitted.footer.chars().count()
+            + fitted
+                .fields
+                .iter()
+                .map(|(_, name, value, _)| name.chars().count() + value.chars().count())
+                .sum::<usize>();
+
+        assert_eq!(total, MAX_EMBED_LEN);
+        assert!(fitted.description.ends_with(ELLIPSIS));
+        assert_eq!(text.truncated_parts(), vec!["description"]);
+    }
+}
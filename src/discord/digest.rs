@@ -0,0 +1,312 @@
+use super::delivery::{get_reaction_users, queue_delivery, DELIVERY_INTERVAL};
+use super::discussion::message_link;
+use super::time::{format_time, parse_event_time_from};
+use super::{
+    event_guild_id, get_config, get_messages, get_scheduler, interested_emoji, parse_reaction_type,
+    user_timezone,
+};
+use crate::database::models::Event;
+use crate::database::{
+    get_all_events, get_digest_users, get_guild_settings, set_guild_digest_message,
+};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use serenity::builder::CreateEmbed;
+use serenity::framework::standard::CommandError;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, GuildId, MessageId};
+use serenity::prelude::{RwLock, ShareMap};
+use serenity::utils::Colour;
+use std::sync::Arc;
+use std::thread;
+use white_rabbit::DateResult;
+
+/// How many days ahead the digest lists events
+const DIGEST_DAYS: i64 = 7;
+/// Longest embed description Discord allows
+const MAX_DIGEST_LEN: usize = 2048;
+
+/// An event listed in the digest
+struct DigestEntry {
+    event: Event,
+    link: String,
+    interested: usize,
+}
+
+/// Schedules the weekly digest to be sent on the configured schedule, like every "monday 9am"
+pub fn schedule_digest(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>) {
+    let config = get_config(data).unwrap();
+
+    let first_time =
+        match next_digest_time(&config.event_timezone, &config.digest_schedule, Utc::now()) {
+            Some(first_time) => first_time,
+            None => {
+                error!(
+                    "Invalid digest_schedule '{}', the digest will not be sent",
+                    config.digest_schedule
+                );
+                return;
+            }
+        };
+
+    let scheduler = get_scheduler(data).unwrap();
+    let mut scheduler = scheduler.write();
+    let http = http.clone();
+    let data = data.clone();
+
+    scheduler.add_task_datetime(first_time, move |_| {
+        if let Err(e) = send_digest(&http, &data) {
+            error!("Unable to send the weekly digest: {}", e.0);
+        }
+
+        match next_digest_time(&config.event_timezone, &config.digest_schedule, Utc::now()) {
+            Some(next_time) if next_time > Utc::now() => DateResult::Repeat(next_time),
+            _ => DateResult::Done,
+        }
+    });
+}
+
+/// Gets the next time after `now` the digest should be sent on a schedule like "monday 9am"
+fn next_digest_time(tz: &Tz, schedule: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_event_time_from(tz, now, schedule).map(|time| DateTime::<Utc>::from_utc(time, Utc))
+}
+
+/// Posts the digest of upcoming events in the digest channel and DMs it to users who want it
+fn send_digest(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>) -> Result<(), CommandError> {
+    let config = get_config(data)?;
+    let guild_id = event_guild_id(http, &config)
+        .ok_or(CommandError("Unable to find the event guild".to_string()))?;
+    let messages = get_messages(data, Some(guild_id))?;
+    let entries = upcoming_entries(http, &config, guild_id)?;
+
+    if let Some(digest_channel) = config.digest_channel {
+        post_digest(
+            http,
+            &config,
+            &messages,
+            guild_id,
+            ChannelId(digest_channel),
+            &entries,
+        )?;
+    }
+
+    let users = get_digest_users(config.db_url.clone())?;
+    if users.is_empty() {
+        return Ok(());
+    }
+
+    let http = http.clone();
+    queue_delivery(data, move || {
+        for user_id in users {
+            let user = match user_id.parse::<u64>().map(|user_id| http.get_user(user_id)) {
+                Ok(Ok(user)) => user,
+                _ => continue,
+            };
+            let tz = user_timezone(&config, &user);
+
+            let result = user.create_dm_channel(&http).and_then(|dm_channel| {
+                dm_channel.send_message(&http, |m| {
+                    m.embed(|e| build_digest_embed(e, &messages, &tz, &entries))
+                })
+            });
+
+            if let Err(e) = result {
+                warn!("Unable to DM the weekly digest to {}: {}", user.tag(), e);
+            }
+
+            thread::sleep(DELIVERY_INTERVAL);
+        }
+    });
+
+    Ok(())
+}
+
+/// Posts the digest in a channel
+///
+/// With `digest_pin`, the pinned digest from last time is updated instead, and a new one is posted
+/// and pinned if it is gone.
+fn post_digest(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    guild_id: GuildId,
+    channel_id: ChannelId,
+    entries: &[DigestEntry],
+) -> Result<(), CommandError> {
+    let tz = config.event_timezone;
+
+    if config.digest_pin {
+        let pinned = get_guild_settings(config.db_url.clone(), guild_id.0.to_string())
+            .ok()
+            .and_then(|settings| settings.digest_message_id)
+            .and_then(|message_id| message_id.parse::<u64>().ok());
+
+        if let Some(message_id) = pinned {
+            let edited = channel_id.edit_message(http, MessageId(message_id), |m| {
+                m.embed(|e| build_digest_embed(e, messages, &tz, entries))
+            });
+
+            if edited.is_ok() {
+                return Ok(());
+            }
+        }
+    }
+
+    let digest_msg = channel_id.send_message(http, |m| {
+        m.embed(|e| build_digest_embed(e, messages, &tz, entries))
+    })?;
+
+    if config.digest_pin {
+        digest_msg.pin(http)?;
+        set_guild_digest_message(
+            config.db_url.clone(),
+            guild_id.0.to_string(),
+            Some(digest_msg.id.0.to_string()),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Gets the posted events starting in the next week, with links and how many are interested
+fn upcoming_entries(
+    http: &Http,
+    config: &HypeBotConfig,
+    guild_id: GuildId,
+) -> Result<Vec<DigestEntry>, CommandError> {
+    let now = Utc::now().naive_utc();
+    let until = now + Duration::days(DIGEST_DAYS);
+
+    let entries = get_all_events(config.db_url.clone())?
+        .into_iter()
+        .filter(|event| {
+            event.publish_time.is_none() && event.event_time > now && event.event_time < until
+        })
+        .filter_map(|event| {
+            let message_id = MessageId(event.message_id.parse::<u64>().ok()?);
+            let interested = get_reaction_users(
+                http,
+                ChannelId(config.event_channel),
+                message_id,
+                parse_reaction_type(&interested_emoji(config, &event.interested_emoji)),
+            )
+            .len();
+
+            Some(DigestEntry {
+                link: message_link(guild_id, ChannelId(config.event_channel), message_id),
+                interested,
+                event,
+            })
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Builds the digest embed, with times in a timezone
+fn build_digest_embed<'a>(
+    e: &'a mut CreateEmbed,
+    messages: &Messages,
+    tz: &Tz,
+    entries: &[DigestEntry],
+) -> &'a mut CreateEmbed {
+    let mut description = String::new();
+
+    for entry in entries {
+        let line = messages.get(
+            "digest_event",
+            &[
+                ("event", &entry.event.event_name),
+                ("link", &entry.link),
+                ("time", &format_time(messages, tz, entry.event.event_time)),
+                ("interested", &entry.interested.to_string()),
+            ],
+        );
+
+        // Leave out the latest events if they do not fit in the embed
+        if description.len() + line.len() + 2 > MAX_DIGEST_LEN {
+            break;
+        }
+        description.push_str(&line);
+        description.push_str("\n\n");
+    }
+
+    if description.is_empty() {
+        description = messages.get("digest_empty", &[]);
+    }
+
+    e.title(messages.get("digest_title", &[]))
+        .color(Colour::PURPLE)
+        .description(description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        DateTime::<Utc>::from_utc(
+            NaiveDate::from_ymd(2020, month, day).and_hms(hour, minute, 0),
+            Utc,
+        )
+    }
+
+    #[test]
+    fn next_digest_is_the_coming_scheduled_day() {
+        // Wednesday 4 November, Monday 9am in New York is 14:00 UTC
+        let tz = chrono_tz::America::New_York;
+        assert_eq!(
+            next_digest_time(&tz, "monday 9am", utc(11, 4, 15, 0)),
+            Some(utc(11, 9, 14, 0))
+        );
+    }
+
+    #[test]
+    fn next_digest_after_sending_is_a_week_later() {
+        let tz = chrono_tz::America::New_York;
+        assert_eq!(
+            next_digest_time(&tz, "monday 9am", utc(11, 9, 14, 0)),
+            Some(utc(11, 16, 14, 0))
+        );
+        assert_eq!(
+            next_digest_time(&tz, "monday 9am", utc(11, 9, 14, 1)),
+            Some(utc(11, 16, 14, 0))
+        );
+    }
+
+    #[test]
+    fn next_digest_is_later_today_if_not_yet_sent() {
+        let tz = chrono_tz::America::New_York;
+        assert_eq!(
+            next_digest_time(&tz, "monday 9am", utc(11, 9, 13, 0)),
+            Some(utc(11, 9, 14, 0))
+        );
+    }
+
+    #[test]
+    fn next_digest_follows_daylight_saving_time() {
+        // Daylight saving time ends on Sunday 1 November, moving 9am from 13:00 to 14:00 UTC
+        let tz = chrono_tz::America::New_York;
+        assert_eq!(
+            next_digest_time(&tz, "monday 9am", utc(10, 28, 12, 0)),
+            Some(utc(11, 2, 14, 0))
+        );
+        assert_eq!(
+            next_digest_time(&tz, "monday 9am", utc(10, 26, 13, 0)),
+            Some(utc(11, 2, 14, 0))
+        );
+    }
+
+    #[test]
+    fn invalid_schedule_has_no_next_digest() {
+        let tz = chrono_tz::America::New_York;
+        assert_eq!(
+            next_digest_time(&tz, "every monday", utc(11, 4, 15, 0)),
+            None
+        );
+        assert_eq!(next_digest_time(&tz, "monday", utc(11, 4, 15, 0)), None);
+    }
+}
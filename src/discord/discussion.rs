@@ -0,0 +1,127 @@
+use super::time::format_time;
+use super::{edit_event_msg, event_guild_id};
+use crate::database::models::Event;
+use crate::database::{get_event_by_id, set_event_discussion};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use serenity::framework::standard::CommandError;
+use serenity::http::request::RequestBuilder;
+use serenity::http::routing::RouteInfo;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, GuildId, MessageId};
+
+/// Posts the message starting the discussion of a newly posted event and links it in the
+/// announcement
+///
+/// Discussions are kept in the discussion channel, does nothing if there is none.
+pub fn start_discussion(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+) -> Result<(), CommandError> {
+    let discussion_channel = match config.discussion_channel {
+        Some(discussion_channel) => ChannelId(discussion_channel),
+        None => return Ok(()),
+    };
+    let guild_id = event_guild_id(http, config)
+        .ok_or(CommandError("Unable to find the event guild".to_string()))?;
+
+    let announcement_link = match event.message_id.parse::<u64>() {
+        Ok(message_id) => message_link(
+            guild_id,
+            ChannelId(config.event_channel),
+            MessageId(message_id),
+        ),
+        Err(_) => String::new(),
+    };
+
+    let start_msg = discussion_channel.say(
+        http,
+        messages.get(
+            "discussion_start",
+            &[
+                ("event", &event.event_name),
+                (
+                    "time",
+                    &format_time(messages, &config.event_timezone, event.event_time),
+                ),
+                ("link", &announcement_link),
+            ],
+        ),
+    )?;
+
+    set_event_discussion(
+        config.db_url.clone(),
+        event.id,
+        Some(message_link(guild_id, discussion_channel, start_msg.id)),
+    )?;
+
+    let event = get_event_by_id(config.db_url.clone(), event.id)?;
+    edit_event_msg(http, config, messages, &event)?;
+
+    Ok(())
+}
+
+/// Posts a message about an event in its discussion, as a reply to the message that started it
+pub fn post_to_discussion(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+    message: &str,
+) {
+    let (discussion_channel, link) = match (config.discussion_channel, &event.discussion_link) {
+        (Some(discussion_channel), Some(link)) => (ChannelId(discussion_channel), link),
+        _ => return,
+    };
+
+    let post = messages.get("discussion_post", &[("message", message), ("link", link)]);
+    let start_id = link
+        .rsplit('/')
+        .next()
+        .and_then(|message_id| message_id.parse::<u64>().ok());
+
+    if let Err(e) = send_reply(http, discussion_channel, start_id, &post) {
+        error!(
+            "Unable to post in the discussion of {}: {}",
+            event.event_name, e
+        );
+    }
+}
+
+/// Sends a message replying to another one, or a plain message without one to reply to
+///
+/// This version of serenity can not send replies, so the request is built by hand. The reply is not
+/// read back since serenity can not parse reply messages either.
+fn send_reply(
+    http: &Http,
+    channel_id: ChannelId,
+    reply_to: Option<u64>,
+    content: &str,
+) -> serenity::Result<()> {
+    let mut body = serde_json::json!({ "content": content });
+    if let Some(message_id) = reply_to {
+        body["message_reference"] = serde_json::json!({
+            "message_id": message_id.to_string(),
+            "fail_if_not_exists": false,
+        });
+    }
+    let body = serde_json::to_vec(&body)?;
+
+    let mut request = RequestBuilder::new(RouteInfo::CreateMessage {
+        channel_id: channel_id.0,
+    });
+    request.body(Some(&body));
+    http.request(request.build())?;
+
+    Ok(())
+}
+
+/// Builds a link to a message
+pub fn message_link(guild_id: GuildId, channel_id: ChannelId, message_id: MessageId) -> String {
+    format!(
+        "https://discord.com/channels/{}/{}/{}",
+        guild_id.0, channel_id.0, message_id.0
+    )
+}
@@ -1,94 +1,500 @@
-use super::{get_config, send_event_msg};
-use crate::database::{get_event_by_name, insert_event, remove_event};
+use super::calendar::update_calendar;
+use super::channels::{parse_channels_lead, remove_event_channels};
+use super::conflicts::{blocks_conflicts, conflict_warning, find_conflicts};
+use super::delivery::{deliver_event_dms, get_reaction_users, EventDms};
+use super::descriptions::{attached_description, length_error, take_code_block};
+use super::discussion::post_to_discussion;
+use super::get_config;
+use super::images::{attached_image, is_attachment_link, parse_image_style};
+use super::options::{parse_flag, take_options};
+use super::permissions::{can_manage_event, permission_role_names, Permission};
+use super::reminders::ReminderMode;
+use super::roles::{delete_attendee_role, rename_attendee_role};
+use super::tags::{event_tags, parse_tags, tag_thumbnail, unknown_tags};
+use super::time::{format_time, parse_event_time};
+use crate::database::models::{Event, NewEvent, PastEvent};
+use crate::database::{
+    clear_event_tags, get_all_events, get_event_by_id, get_event_by_name, get_event_image,
+    get_past_event_by_name, insert_event, insert_event_image, remove_event, update_event,
+};
 use crate::discord::{
-    get_draft_event, schedule_event, send_dm_message, send_draft_event, update_draft_event,
+    check_event_emoji, edit_event_msg, get_draft_event, get_messages, interested_emoji,
+    parse_reaction_type, publish_event, publish_pending_event, replace_event_image, schedule_event,
+    schedule_publish, send_draft_event, update_draft_event, Notification,
 };
-use crate::INTERESTED_EMOJI;
-use chrono::offset::TimeZone;
-use chrono::{Datelike, NaiveDateTime, Timelike, Utc};
-use chrono_tz::Tz;
-use serenity::framework::standard::{macros::command, Args, CommandResult};
-use serenity::model::prelude::{Mentionable, Message, User};
+use chrono::Utc;
+use serenity::framework::standard::{macros::command, Args, CommandError, CommandResult};
+use serenity::model::prelude::{Mentionable, Message};
 use serenity::prelude::Context;
 use serenity::utils::{content_safe, ContentSafeOptions};
 use url::Url;
 
+/// Options `create` takes as `key:value` after the event's arguments
+pub const EVENT_OPTIONS: [&str; 8] = [
+    "interested",
+    "uninterested",
+    "reminders",
+    "role",
+    "channels",
+    "survey",
+    "tags",
+    "image",
+];
+
 #[command]
 /// Posts a previewed event
 ///
-/// `~confirm`
+/// `~confirm` or `~confirm at "monday 9am"`
+///
+/// **Delayed posting**
+/// With `at`, the event is held back and posted at the given time. Use `pending` to see held back
+/// events and `publish "event name"` to post one early.
 ///
 /// **Note**
-/// You can only post events you have created. Only one preview event can exist at a time.
-fn confirm(ctx: &mut Context, msg: &Message, _args: Args) -> CommandResult {
+/// You can only post events you have created. Each user has one preview event, a new one
+/// replaces it.
+fn confirm(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
     let config = get_config(&ctx.data)?;
-    let draft_event = get_draft_event(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
 
-    let mut new_event = draft_event.event.clone();
-    // Check to to see if message author is the owner of the pending event
-    if draft_event.creator_id == msg.author.id.0 {
-        // Send event message
-        let event_msg = send_event_msg(&ctx.http, &config, config.event_channel, &new_event, true)?;
+    let draft_event = match get_draft_event(&ctx.data, msg.author.id.0)? {
+        Some(draft_event) => draft_event,
+        None => {
+            msg.reply(&ctx, messages.get("no_draft", &[]))?;
+            return Ok(());
+        }
+    };
 
-        msg.reply(&ctx, "Event posted!")?;
+    // Other events may have been scheduled since the draft was previewed
+    if is_blocked_by_conflicts(ctx, msg, &draft_event.event, None)? {
+        return Ok(());
+    }
 
-        new_event.message_id = event_msg.id.0.to_string();
+    if args.single::<String>().ok().as_deref() == Some("at") {
+        let publish_time =
+            match parse_event_time(&config.event_timezone, &args.rest().replace("\"", "")) {
+                Some(publish_time) => publish_time,
+                None => {
+                    msg.reply(&ctx, messages.get("time_format_help", &[]))?;
+                    return Ok(());
+                }
+            };
+
+        if Utc::now().naive_utc() > publish_time {
+            msg.reply(&ctx, messages.get("publish_time_passed", &[]))?;
+            return Ok(());
+        }
+
+        if publish_time > draft_event.event.event_time {
+            msg.reply(&ctx, messages.get("publish_after_start", &[]))?;
+            return Ok(());
+        }
+
+        let mut new_event = draft_event.event.clone();
+        new_event.publish_time = Some(publish_time);
 
         let event = insert_event(config.db_url.clone(), &new_event)?;
 
-        schedule_event(&ctx.http, &ctx.data, &event);
+        // Keep the image until it can be uploaded with the announcement
+        if let Some(mut image) = draft_event.image {
+            image.event_id = event.id;
+            insert_event_image(config.db_url.clone(), &image)?;
+        }
+
+        schedule_publish(&ctx.http, &ctx.data, &event);
+
+        msg.reply(
+            &ctx,
+            messages.get(
+                "event_pending",
+                &[
+                    ("event", &event.event_name),
+                    (
+                        "time",
+                        &format_time(&messages, &config.event_timezone, publish_time),
+                    ),
+                ],
+            ),
+        )?;
     } else {
-        msg.reply(&ctx, format!("You do not have a pending event!"))?;
+        publish_event(ctx, &draft_event.event, draft_event.image.as_ref())?;
+
+        msg.reply(&ctx, messages.get("event_posted", &[]))?;
     }
 
     Ok(())
 }
 
+#[command]
+/// Lists events that are waiting to be posted
+///
+/// `~pending`
+fn pending(ctx: &mut Context, msg: &Message, _args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let pending_events: Vec<String> = get_all_events(config.db_url.clone())?
+        .into_iter()
+        .filter_map(|event| {
+            event.publish_time.map(|publish_time| {
+                messages.get(
+                    "pending_event",
+                    &[
+                        ("event", &event.event_name),
+                        (
+                            "time",
+                            &format_time(&messages, &config.event_timezone, publish_time),
+                        ),
+                    ],
+                )
+            })
+        })
+        .collect();
+
+    if pending_events.is_empty() {
+        msg.reply(&ctx, messages.get("pending_empty", &[]))?;
+    } else {
+        msg.channel_id.say(&ctx, pending_events.join("\n"))?;
+    }
+
+    Ok(())
+}
+
+#[command]
+/// Posts an event that is waiting to be posted right away
+///
+/// `~publish "event name"`
+fn publish(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    // Parse args
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+
+    let event = match get_event_by_name(config.db_url.clone(), event_name) {
+        Ok(event) if event.publish_time.is_some() => event,
+        _ => {
+            msg.reply(&ctx, messages.get("pending_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    if !check_event_permission(ctx, msg, &event, Permission::Edit)? {
+        return Ok(());
+    }
+
+    if Utc::now().naive_utc() > event.event_time {
+        msg.reply(&ctx, messages.get("publish_started", &[]))?;
+        return Ok(());
+    }
+
+    publish_pending_event(&ctx.http, &ctx.data, &event)?;
+
+    msg.reply(&ctx, messages.get("event_posted", &[]))?;
+
+    Ok(())
+}
+
 #[command]
 /// Creates an event and previews the announcement
 ///
 /// `~create "event name" "04:20pm 2069-04-20" "event description" "http://optional.thumbnail.link" "optional organizer"`
 ///
 /// **Time format**
-/// The time format is HH:MMam YYYY-MM-DD
+/// The time format is HH:MMam YYYY-MM-DD, or a day and time like "monday 9am"
 ///
 /// **Thumbnail Link**
 /// The thumbnail link is optional, if one is not provided, a default image is shown
 ///
 /// **Organizer**
 /// The user or group that is organizing the event, defaults to the user creating the event
-fn create(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+///
+/// **Reaction emojis**
+/// `interested:🎉` and `uninterested:😴` change the reactions used for this event, custom emojis
+/// of this server are supported
+///
+/// **Reminders**
+/// `reminders:dm`, `reminders:channel` or `reminders:both` choose how reminders are sent for this
+/// event, defaults to the server's reminder mode
+///
+/// **Attendee role**
+/// `role:yes` gives everyone interested a role for the event, which is mentioned in channel
+/// reminders and deleted after the event
+///
+/// **Channels**
+/// `channels:yes` creates a voice and text channel for the event when it is posted, or
+/// `channels:30` creates them 30 minutes before it starts
+///
+/// **Survey**
+/// `survey:yes` DMs attendees a survey after the event, see the results with `feedback`
+///
+/// **Long descriptions**
+/// For a description with several lines, put it in a code block instead of quotes, or leave it out
+/// and attach it as a `.txt` file. The name, description, location and organizer have to fit in a
+/// Discord embed
+///
+/// **Tags**
+/// `tags:gaming,social` tags the event, its color and default thumbnail come from the first tag
+/// and everyone subscribed to a tag is DMed when it is posted
+///
+/// **Image**
+/// Attach an image to the message to show it as a banner across the announcement, or use
+/// `image:thumbnail` to show it as the thumbnail instead. PNG, JPG, GIF and WEBP images up to 8 MB
+/// are supported, the image is uploaded again with the announcement so it stays if your message is
+/// deleted
+fn create(ctx: &mut Context, msg: &Message, args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let (args, description) = take_code_block(&args);
+    let (mut args, options) = take_options(&args, &EVENT_OPTIONS);
+
+    let mut new_event = match parse_event_args(ctx, msg, &mut args, description)? {
+        Some(new_event) => new_event,
+        None => return Ok(()),
+    };
+
+    for emoji in [options.get("interested"), options.get("uninterested")]
+        .iter()
+        .flatten()
+    {
+        if let Err(e) = check_event_emoji(ctx, &config, emoji) {
+            msg.reply(&ctx, messages.get(e, &[("emoji", emoji)]))?;
+            return Ok(());
+        }
+    }
+
+    new_event.interested_emoji = options.get("interested").cloned();
+    new_event.uninterested_emoji = options.get("uninterested").cloned();
+
+    if let Some(mode) = options.get("reminders") {
+        match mode.parse::<ReminderMode>() {
+            Ok(mode) => new_event.reminder_mode = Some(mode.as_str().to_string()),
+            Err(e) => {
+                msg.reply(&ctx, messages.get(e, &[]))?;
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(role) = options.get("role") {
+        match parse_flag(role) {
+            Some(role) => new_event.attendee_role = role,
+            None => {
+                msg.reply(&ctx, messages.get("invalid_role_option", &[]))?;
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(survey) = options.get("survey") {
+        match parse_flag(survey) {
+            Some(survey) => new_event.survey = survey,
+            None => {
+                msg.reply(&ctx, messages.get("invalid_survey_option", &[]))?;
+                return Ok(());
+            }
+        }
+    }
+
+    if let Some(tags) = options.get("tags") {
+        let tags = parse_tags(tags);
+        if let Some(unknown) = unknown_tags(&config, &messages, &tags) {
+            msg.reply(&ctx, unknown)?;
+            return Ok(());
+        }
+
+        // A thumbnail given with the event wins over the tag's
+        if new_event.thumbnail_link == config.default_thumbnail_link {
+            if let Some(thumbnail) = tag_thumbnail(&config, &tags) {
+                new_event.thumbnail_link = thumbnail;
+            }
+        }
+
+        if !tags.is_empty() {
+            new_event.tags = Some(tags.join(","));
+        }
+    }
+
+    if let Some(channels) = options.get("channels") {
+        match parse_channels_lead(channels) {
+            Some(lead) => new_event.channels_lead = lead,
+            None => {
+                msg.reply(&ctx, messages.get("invalid_channels_option", &[]))?;
+                return Ok(());
+            }
+        }
+    }
+
+    let banner = match options.get("image").map(|image| parse_image_style(image)) {
+        Some(Some(banner)) => banner,
+        Some(None) => {
+            msg.reply(&ctx, messages.get("invalid_image_option", &[]))?;
+            return Ok(());
+        }
+        None => true,
+    };
+
+    let image = match attached_image(msg, banner) {
+        Ok(image) => image,
+        Err(e) => {
+            msg.reply(&ctx, e.describe(&messages))?;
+            return Ok(());
+        }
+    };
+
+    if is_blocked_by_conflicts(ctx, msg, &new_event, None)? {
+        return Ok(());
+    }
+
+    update_draft_event(&ctx.data, new_event, image, msg.author.id.0)?;
+    send_draft_event(&ctx.http, &ctx.data, msg.channel_id, msg.author.id.0)?;
+
+    Ok(())
+}
+
+#[command("clone")]
+/// Copies an event into a new draft at another time, to preview and `confirm`
+///
+/// `~clone "event name" when:"next saturday 7pm"`
+///
+/// **Note**
+/// Events that are over can be cloned too, the latest one with the name is used. The new event
+/// gets its own announcement, role and channels. An uploaded image is copied from events that are
+/// not over yet, events that are over lose it.
+fn clone_event(ctx: &mut Context, msg: &Message, args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let (mut args, options) = take_options(&args, &["when"]);
+
+    let event_name = match args.single_quoted::<String>() {
+        Ok(event_name) => event_name.replace("\"", ""),
+        Err(_) => {
+            msg.reply(&ctx, messages.get("no_event_name", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let when = match options.get("when") {
+        Some(when) => when,
+        None => {
+            msg.reply(&ctx, messages.get("clone_no_time", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let (past_event, image) = match get_event_by_name(config.db_url.clone(), event_name.clone()) {
+        Ok(event) => {
+            let image = get_event_image(config.db_url.clone(), event.id)
+                .ok()
+                .map(|mut image| {
+                    image.event_id = 0;
+                    image
+                });
+            (PastEvent::from(event), image)
+        }
+        Err(_) => match get_past_event_by_name(config.db_url.clone(), event_name) {
+            Ok(past_event) => (past_event, None),
+            Err(_) => {
+                msg.reply(&ctx, messages.get("event_not_found", &[]))?;
+                return Ok(());
+            }
+        },
+    };
+
+    let event_time = match parse_event_time(&config.event_timezone, when) {
+        Some(event_time) => event_time,
+        None => {
+            msg.reply(&ctx, messages.get("time_format_help", &[]))?;
+            return Ok(());
+        }
+    };
+
+    if Utc::now().naive_utc() > event_time {
+        msg.reply(&ctx, messages.get("time_passed", &[]))?;
+        return Ok(());
+    }
+
+    let mut new_event: NewEvent = past_event.into();
+    new_event.event_time = event_time;
+
+    // Links to the old announcement's attachments break once it is deleted
+    if is_attachment_link(&new_event.thumbnail_link) {
+        new_event.thumbnail_link = tag_thumbnail(&config, &event_tags(&new_event.tags))
+            .unwrap_or_else(|| config.default_thumbnail_link.clone());
+    }
+    if new_event
+        .image_link
+        .as_ref()
+        .map(|link| is_attachment_link(link))
+        .unwrap_or(false)
+    {
+        new_event.image_link = None;
+    }
+
+    if is_blocked_by_conflicts(ctx, msg, &new_event, None)? {
+        return Ok(());
+    }
+
+    update_draft_event(&ctx.data, new_event, image, msg.author.id.0)?;
+    send_draft_event(&ctx.http, &ctx.data, msg.channel_id, msg.author.id.0)?;
+
+    Ok(())
+}
+
+/// Parses the arguments shared by `create` and `propose` into a new event
+///
+/// Returns `None` if the arguments were invalid, the user has already been told why.
+///
+/// The description can be passed in from a code block, otherwise it is read from an attached `.txt`
+/// file or the arguments.
+pub fn parse_event_args(
+    ctx: &Context,
+    msg: &Message,
+    args: &mut Args,
+    description: Option<String>,
+) -> Result<Option<NewEvent>, CommandError> {
     // Get config
     let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
 
     // Parse args
     let event_name = match args.find::<String>() {
         Ok(event_name) => event_name.replace("\"", ""),
         Err(_) => {
-            msg.reply(&ctx, "No event name provided.".to_string())?;
-            return Ok(());
+            msg.reply(ctx, messages.get("no_event_name", &[]))?;
+            return Ok(None);
         }
     };
     let date_string = match args.find::<String>() {
         Ok(date_string) => date_string.replace("\"", ""),
         Err(_) => {
-            msg.reply(&ctx, "No date provided.".to_string())?;
-            return Ok(());
+            msg.reply(ctx, messages.get("no_date", &[]))?;
+            return Ok(None);
         }
     };
-    let description = match args.find::<String>() {
-        Ok(desc) => desc.replace("\"", ""),
-        Err(_) => {
-            msg.reply(&ctx, "No description provided.".to_string())?;
-            return Ok(());
-        }
+    let description = match description {
+        Some(description) => description,
+        None => match attached_description(msg) {
+            Ok(Some(description)) => description,
+            Ok(None) => match args.find::<String>() {
+                Ok(desc) => desc.replace("\"", ""),
+                Err(_) => {
+                    msg.reply(ctx, messages.get("no_description", &[]))?;
+                    return Ok(None);
+                }
+            },
+            Err(e) => {
+                msg.reply(ctx, e.describe(&messages))?;
+                return Ok(None);
+            }
+        },
     };
 
     let location = match args.find::<String>() {
         Ok(desc) => desc.replace("\"", ""),
         Err(_) => {
-            msg.reply(&ctx, "No location provided.".to_string())?;
-            return Ok(());
+            msg.reply(ctx, messages.get("no_location", &[]))?;
+            return Ok(None);
         }
     };
 
@@ -103,35 +509,17 @@ fn create(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
     };
 
     // Parse date
-    let tz: Tz = config.event_timezone;
-    let input_date = match NaiveDateTime::parse_from_str(date_string.as_str(), "%I:%M%P %Y-%m-%d") {
-        Ok(date) => date,
-        Err(_) => {
-            msg.reply(
-                &ctx,
-                "Invalid date format. Format is HH:MMam YYYY-MM-DD".to_string(),
-            )?;
-            return Ok(());
+    let event_time = match parse_event_time(&config.event_timezone, &date_string) {
+        Some(event_time) => event_time,
+        None => {
+            msg.reply(ctx, messages.get("time_format_help", &[]))?;
+            return Ok(None);
         }
     };
 
-    let input_date = tz
-        .ymd(
-            input_date.date().year(),
-            input_date.date().month(),
-            input_date.date().day(),
-        )
-        .and_hms(
-            input_date.time().hour(),
-            input_date.time().minute(),
-            input_date.time().second(),
-        );
-
-    let event_time = input_date.with_timezone(&Utc).naive_utc();
-
     if Utc::now().naive_utc() > event_time {
-        msg.reply(&ctx, "The scheduled time has already passed!")?;
-        return Ok(());
+        msg.reply(ctx, messages.get("time_passed", &[]))?;
+        return Ok(None);
     }
 
     // Clean channel, role, and everyone pings
@@ -146,46 +534,421 @@ fn create(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
     let location = content_safe(&ctx.cache, location, &settings);
     let organizer = content_safe(&ctx.cache, organizer, &settings);
 
-    update_draft_event(
-        &ctx,
+    let new_event = NewEvent {
         event_name,
-        description,
+        event_desc: description,
+        event_loc: location,
         organizer,
-        location,
-        thumbnail_link,
         event_time,
-        msg.author.id.0,
+        message_id: String::new(),
+        thumbnail_link,
+        reminder_sent: 0,
+        creator_id: msg.author.id.0.to_string(),
+        publish_time: None,
+        interested_emoji: None,
+        uninterested_emoji: None,
+        reminder_mode: None,
+        attendee_role: false,
+        role_id: None,
+        channels_lead: None,
+        voice_channel_id: None,
+        text_channel_id: None,
+        discussion_link: None,
+        survey: false,
+        tags: None,
+        image_link: None,
+    };
+
+    if let Some(e) = length_error(&messages, &new_event) {
+        msg.reply(ctx, e)?;
+        return Ok(None);
+    }
+
+    Ok(Some(new_event))
+}
+
+#[command]
+/// Edits an already scheduled event
+///
+/// `~edit "event name" field "new value"`
+///
+/// **Fields**
+/// `name`, `time`, `description`, `location`, `thumbnail`, `organizer`, `tags`, `reminders`, or
+/// `image`
+///
+/// A description with several lines can be given in a code block instead of quotes. For `image`,
+/// attach the new image and give `banner` or `thumbnail` as the value.
+///
+/// **Note**
+/// Organizers can always edit their own events.
+fn edit(ctx: &mut Context, msg: &Message, args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    // Parse args, the new value can be a code block to keep its line breaks
+    let (mut args, code_block) = take_code_block(&args);
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+    let field = args.single_quoted::<String>()?.to_lowercase();
+    let value = match code_block {
+        Some(value) => value,
+        None => args.single_quoted::<String>()?.replace("\"", ""),
+    };
+
+    let event = match get_event_by_name(config.db_url.clone(), event_name) {
+        Ok(event) => event,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("event_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    if !check_event_permission(ctx, msg, &event, Permission::Edit)? {
+        return Ok(());
+    }
+
+    // Clean channel, role, and everyone pings
+    let settings = ContentSafeOptions::default()
+        .clean_role(true)
+        .clean_here(true)
+        .clean_user(false)
+        .clean_everyone(true);
+    let value = content_safe(&ctx.cache, value, &settings);
+
+    let mut new_event: NewEvent = event.clone().into();
+    let mut image = None;
+    match field.as_str() {
+        "name" => new_event.event_name = value,
+        "description" => new_event.event_desc = value,
+        "location" => new_event.event_loc = value,
+        "organizer" => new_event.organizer = value,
+        "thumbnail" => match Url::parse(&value) {
+            Ok(link) => new_event.thumbnail_link = link.into_string(),
+            Err(_) => {
+                msg.reply(&ctx, messages.get("invalid_thumbnail", &[]))?;
+                return Ok(());
+            }
+        },
+        "tags" => {
+            let tags = parse_tags(&value);
+            if let Some(unknown) = unknown_tags(&config, &messages, &tags) {
+                msg.reply(&ctx, unknown)?;
+                return Ok(());
+            }
+
+            // A thumbnail that came from the old tags follows the new ones, one given by hand stays
+            let old_thumbnail = tag_thumbnail(&config, &event_tags(&event.tags));
+            if new_event.thumbnail_link == config.default_thumbnail_link
+                || old_thumbnail.as_ref() == Some(&new_event.thumbnail_link)
+            {
+                new_event.thumbnail_link = tag_thumbnail(&config, &tags)
+                    .unwrap_or_else(|| config.default_thumbnail_link.clone());
+            }
+
+            new_event.tags = if tags.is_empty() {
+                None
+            } else {
+                Some(tags.join(","))
+            };
+        }
+        "reminders" => match value.parse::<ReminderMode>() {
+            Ok(mode) => new_event.reminder_mode = Some(mode.as_str().to_string()),
+            Err(e) => {
+                msg.reply(&ctx, messages.get(e, &[]))?;
+                return Ok(());
+            }
+        },
+        "image" => {
+            let banner = match parse_image_style(&value) {
+                Some(banner) => banner,
+                None => {
+                    msg.reply(&ctx, messages.get("invalid_image_style", &[]))?;
+                    return Ok(());
+                }
+            };
+
+            match attached_image(msg, banner) {
+                Ok(Some(mut attached)) => {
+                    attached.event_id = event.id;
+                    image = Some(attached);
+                }
+                Ok(None) => {
+                    msg.reply(&ctx, messages.get("image_missing", &[]))?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    msg.reply(&ctx, e.describe(&messages))?;
+                    return Ok(());
+                }
+            }
+        }
+        "time" => {
+            let event_time = match parse_event_time(&config.event_timezone, &value) {
+                Some(event_time) => event_time,
+                None => {
+                    msg.reply(&ctx, messages.get("time_format_help", &[]))?;
+                    return Ok(());
+                }
+            };
+
+            if Utc::now().naive_utc() > event_time {
+                msg.reply(&ctx, messages.get("time_passed", &[]))?;
+                return Ok(());
+            }
+
+            if let Some(publish_time) = event.publish_time {
+                if publish_time > event_time {
+                    msg.reply(
+                        &ctx,
+                        messages.get(
+                            "start_before_publish",
+                            &[(
+                                "time",
+                                &format_time(&messages, &config.event_timezone, publish_time),
+                            )],
+                        ),
+                    )?;
+                    return Ok(());
+                }
+            }
+
+            new_event.event_time = event_time;
+            new_event.reminder_sent = 0;
+        }
+        _ => {
+            msg.reply(&ctx, messages.get("unknown_field", &[]))?;
+            return Ok(());
+        }
+    }
+
+    if let Some(e) = length_error(&messages, &new_event) {
+        msg.reply(&ctx, e)?;
+        return Ok(());
+    }
+
+    let conflict_msg = if field == "time" || field == "location" {
+        if is_blocked_by_conflicts(ctx, msg, &new_event, Some(event.id))? {
+            return Ok(());
+        }
+
+        let conflicts = find_conflicts(&config, new_event.event_time, Some(event.id));
+        conflict_warning(&config, &messages, &new_event, &conflicts)
+    } else {
+        None
+    };
+
+    update_event(config.db_url.clone(), event.id, &new_event)?;
+    if field == "tags" && new_event.tags.is_none() {
+        clear_event_tags(config.db_url.clone(), event.id)?;
+    }
+    if let Some(image) = &image {
+        insert_event_image(config.db_url.clone(), image)?;
+    }
+    let event = get_event_by_id(config.db_url.clone(), event.id)?;
+
+    // Events waiting to be posted have no message or reminders yet
+    if event.publish_time.is_none() {
+        match &image {
+            Some(image) => replace_event_image(&ctx.http, &config, &messages, &event, image)?,
+            None => {
+                edit_event_msg(&ctx.http, &config, &messages, &event)?;
+            }
+        }
+
+        if field == "name" {
+            rename_attendee_role(&ctx.http, &config, &messages, &event);
+        }
+
+        if field == "time" {
+            schedule_event(&ctx.http, &ctx.data, &event);
+        }
+
+        if field == "name" || field == "time" {
+            update_calendar(&ctx.http, &ctx.data);
+        }
+
+        let new_value = match field.as_str() {
+            "name" => event.event_name.clone(),
+            "description" => event.event_desc.clone(),
+            "location" => event.event_loc.clone(),
+            "organizer" => event.organizer.clone(),
+            "thumbnail" => event.thumbnail_link.clone(),
+            "time" => format_time(&messages, &config.event_timezone, event.event_time),
+            "tags" => event.tags.clone().unwrap_or_default(),
+            "image" => image
+                .as_ref()
+                .map(|image| image.filename.clone())
+                .unwrap_or_default(),
+            _ => event.reminder_mode.clone().unwrap_or_default(),
+        };
+        let update_msg = messages.get(
+            "discussion_update",
+            &[
+                ("event", &event.event_name),
+                ("field", &messages.get(&format!("field_{}", field), &[])),
+                ("value", &new_value),
+            ],
+        );
+        post_to_discussion(&ctx.http, &config, &messages, &event, &update_msg);
+    }
+
+    msg.reply(
+        &ctx,
+        messages.get("event_updated", &[("event", &event.event_name)]),
     )?;
-    send_draft_event(&ctx, msg.channel_id)?;
+
+    if let Some(conflict_msg) = conflict_msg {
+        msg.channel_id.say(&ctx.http, conflict_msg)?;
+    }
 
     Ok(())
 }
 
+/// Checks if an event overlaps another event in a server that blocks conflicts, letting the
+/// author know if it does
+fn is_blocked_by_conflicts(
+    ctx: &Context,
+    msg: &Message,
+    event: &NewEvent,
+    exclude: Option<i32>,
+) -> Result<bool, CommandError> {
+    let config = get_config(&ctx.data)?;
+
+    if !blocks_conflicts(&config, msg.guild_id) {
+        return Ok(false);
+    }
+
+    let conflicts = find_conflicts(&config, event.event_time, exclude);
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    match conflict_warning(&config, &messages, event, &conflicts) {
+        Some(warning) => {
+            msg.reply(
+                ctx,
+                format!("{}\n{}", warning, messages.get("conflicts_blocked", &[])),
+            )?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Checks if the message author can manage an event, letting them know if they can not
+fn check_event_permission(
+    ctx: &Context,
+    msg: &Message,
+    event: &Event,
+    permission: Permission,
+) -> Result<bool, CommandError> {
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(false),
+    };
+
+    if can_manage_event(ctx, guild_id, &msg.author, event, permission) {
+        return Ok(true);
+    }
+
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let action = messages.get(permission.action_key(), &[]);
+    let mut roles = permission_role_names(ctx, guild_id, permission);
+    roles.sort();
+    roles.dedup();
+    let reply = if roles.is_empty() {
+        messages.get(
+            "permission_denied_event",
+            &[("action", &action), ("event", &event.event_name)],
+        )
+    } else {
+        messages.get(
+            "permission_denied_event_roles",
+            &[
+                ("action", &action),
+                ("event", &event.event_name),
+                (
+                    "roles",
+                    &roles.join(&format!(" {} ", messages.get("or", &[]))),
+                ),
+            ],
+        )
+    };
+
+    msg.reply(ctx, reply)?;
+    Ok(false)
+}
+
 #[command]
 /// Cancels an already scheduled event
 ///
 /// `~cancel "event name"`
+///
+/// **Note**
+/// Organizers can always cancel their own events.
 fn cancel(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
     let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
 
     // Parse args
     let event_name = args.single::<String>()?.replace("\"", "");
 
-    let event = get_event_by_name(config.db_url.clone(), event_name)?;
+    let event = match get_event_by_name(config.db_url.clone(), event_name) {
+        Ok(event) => event,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("event_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    if !check_event_permission(ctx, msg, &event, Permission::Cancel)? {
+        return Ok(());
+    }
+
+    let cancel_msg = messages.get(
+        "event_canceled",
+        &[
+            ("event", &event.event_name),
+            (
+                "time",
+                &format_time(&messages, &config.event_timezone, event.event_time),
+            ),
+        ],
+    );
+
+    // Events waiting to be posted have no message or reactions yet
+    if event.publish_time.is_some() {
+        remove_event(config.db_url.clone(), event.id)?;
+        msg.reply(&ctx, &cancel_msg)?;
+        return Ok(());
+    }
     let message_id = event.message_id.parse::<u64>()?;
     let message = ctx.http.get_message(config.event_channel, message_id)?;
 
-    let reaction_users = message
-        .reaction_users(&ctx.http, INTERESTED_EMOJI, None, None)
-        .unwrap_or(Vec::<User>::new());
+    let reaction_users = get_reaction_users(
+        &ctx.http,
+        message.channel_id,
+        message.id,
+        parse_reaction_type(&interested_emoji(&config, &event.interested_emoji)),
+    );
 
-    let cancel_msg = format!("**{}** has been canceled!", event.event_name.clone());
+    // Let each reacted user know, in their own timezone
+    deliver_event_dms(
+        &ctx.data,
+        ctx.http.clone(),
+        EventDms {
+            config: config.clone(),
+            messages: messages.clone(),
+            event: event.clone(),
+            users: reaction_users,
+            msg_key: "event_canceled",
+            notification: Notification::Update,
+        },
+    );
 
-    for user in reaction_users {
-        send_dm_message(&ctx.http, user, &cancel_msg);
-    }
+    post_to_discussion(&ctx.http, &config, &messages, &event, &cancel_msg);
 
     remove_event(config.db_url.clone(), event.id)?;
+    delete_attendee_role(&ctx.http, &config, &event);
+    remove_event_channels(&ctx.http, &config, &event);
+    update_calendar(&ctx.http, &ctx.data);
 
     message.delete(&ctx)?;
 
@@ -0,0 +1,225 @@
+use super::descriptions::is_text_file;
+use crate::database::models::{EventImage, NewEvent};
+use crate::messages::Messages;
+use serde_json::Value;
+use serenity::builder::CreateEmbed;
+use serenity::http::request::RequestBuilder;
+use serenity::http::routing::RouteInfo;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, Message};
+use serenity::utils::hashmap_to_json_map;
+use serenity::Error;
+use url::Url;
+
+/// Largest image that can be attached to an event, Discord's upload limit
+const MAX_IMAGE_SIZE: u64 = 8 * 1024 * 1024;
+/// File types that can be shown in an embed
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+/// Separates the parts of an image upload, long enough not to turn up in the image itself
+const UPLOAD_BOUNDARY: &str = "hypebot-event-image-4f9c2e7a1b6d8305";
+/// Hosts Discord serves message attachments from
+const ATTACHMENT_HOSTS: [&str; 2] = ["cdn.discordapp.com", "media.discordapp.net"];
+
+/// Parses the `image` option, `banner` or `thumbnail`, into whether the image is a banner
+pub fn parse_image_style(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "banner" => Some(true),
+        "thumbnail" => Some(false),
+        _ => None,
+    }
+}
+
+/// Why an attached image can not be used
+#[derive(Debug, PartialEq)]
+pub enum ImageError {
+    NotAnImage,
+    TooLarge,
+    Download,
+}
+
+impl ImageError {
+    /// Describes the error to reply with
+    pub fn describe(&self, messages: &Messages) -> String {
+        match self {
+            ImageError::NotAnImage => messages.get(
+                "image_not_an_image",
+                &[("types", &IMAGE_EXTENSIONS.join(", "))],
+            ),
+            ImageError::TooLarge => messages.get(
+                "image_too_large",
+                &[("size", &(MAX_IMAGE_SIZE / 1024 / 1024).to_string())],
+            ),
+            ImageError::Download => messages.get("image_download", &[]),
+        }
+    }
+}
+
+/// Downloads the image attached to a message, `Ok(None)` if there is none
+pub fn attached_image(msg: &Message, banner: bool) -> Result<Option<EventImage>, ImageError> {
+    let attachment = match msg
+        .attachments
+        .iter()
+        .find(|attachment| !is_text_file(&attachment.filename))
+    {
+        Some(attachment) => attachment,
+        None => return Ok(None),
+    };
+
+    let (stem, extension) = match attachment.filename.rfind('.') {
+        Some(index) => (
+            &attachment.filename[..index],
+            attachment.filename[index + 1..].to_lowercase(),
+        ),
+        None => (attachment.filename.as_str(), String::new()),
+    };
+
+    if !IMAGE_EXTENSIONS.contains(&extension.as_str()) || attachment.width.is_none() {
+        return Err(ImageError::NotAnImage);
+    }
+
+    if attachment.size > MAX_IMAGE_SIZE {
+        return Err(ImageError::TooLarge);
+    }
+
+    let data = attachment.download().map_err(|_| ImageError::Download)?;
+
+    Ok(Some(EventImage {
+        event_id: 0,
+        filename: image_filename(stem, &extension),
+        data,
+        banner,
+    }))
+}
+
+/// Makes a filename that can be referenced with `attachment://`, which only allows plain names
+fn image_filename(stem: &str, extension: &str) -> String {
+    let stem: String = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!("{}.{}", stem, extension)
+}
+
+/// Points the event's embed at an image uploaded along with its message
+///
+/// The `attachment://` link only means something in a message carrying the image, so it is never
+/// stored. The announcement keeps its attachment when edited, so edits can point at it again.
+pub fn with_uploaded_image(event: &NewEvent, image: &EventImage) -> NewEvent {
+    let mut event = event.clone();
+    let link = format!("attachment://{}", image.filename);
+
+    if image.banner {
+        event.image_link = Some(link);
+    } else {
+        event.thumbnail_link = link;
+    }
+
+    event
+}
+
+/// Edits a message to show a new embed with a new image, dropping the image it had
+///
+/// This version of serenity can only upload files with new messages, so the request is built by
+/// hand.
+pub fn edit_with_image(
+    http: &Http,
+    channel_id: ChannelId,
+    message_id: u64,
+    embed: CreateEmbed,
+    image: &EventImage,
+) -> serenity::Result<()> {
+    let payload = serde_json::json!({
+        "embed": Value::Object(hashmap_to_json_map(embed.0)),
+        "attachments": [],
+    });
+
+    let mut body = format!(
+        "--{boundary}\r\n\
+         Content-Disposition: form-data; name=\"payload_json\"\r\n\
+         Content-Type: application/json\r\n\r\n\
+         {payload}\r\n\
+         --{boundary}\r\n\
+         Content-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        boundary = UPLOAD_BOUNDARY,
+        payload = payload,
+        filename = image.filename,
+    )
+    .into_bytes();
+    body.extend_from_slice(&image.data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", UPLOAD_BOUNDARY).as_bytes());
+
+    let mut request = RequestBuilder::new(RouteInfo::EditMessage {
+        channel_id: channel_id.0,
+        message_id,
+    });
+    request.body(Some(&body));
+    let mut request = request.build();
+
+    let content_type = format!("multipart/form-data; boundary={}", UPLOAD_BOUNDARY)
+        .parse()
+        .map_err(|_| Error::Other("Invalid upload content type"))?;
+    let headers = request.headers_mut().get_or_insert_with(Default::default);
+    headers.insert("content-type", content_type);
+    headers.insert("content-length", body.len().into());
+
+    http.request(request)?;
+
+    Ok(())
+}
+
+/// Checks if a link points at a file attached to a Discord message, which stops working once the
+/// message is deleted
+pub fn is_attachment_link(link: &str) -> bool {
+    if link.starts_with("attachment://") {
+        return true;
+    }
+
+    match Url::parse(link) {
+        Ok(link) => {
+            ATTACHMENT_HOSTS.contains(&link.host_str().unwrap_or_default())
+                && link.path().starts_with("/attachments/")
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_filenames_are_plain() {
+        assert_eq!(image_filename("my banner (1)", "png"), "my_banner__1_.png");
+        assert_eq!(image_filename("game-night_2", "jpg"), "game-night_2.jpg");
+    }
+
+    #[test]
+    fn discord_attachments_are_attachment_links() {
+        assert!(is_attachment_link("attachment://banner.png"));
+        assert!(is_attachment_link(
+            "https://cdn.discordapp.com/attachments/1/2/banner.png"
+        ));
+        assert!(is_attachment_link(
+            "https://media.discordapp.net/attachments/1/2/banner.png?width=400"
+        ));
+    }
+
+    #[test]
+    fn other_links_are_not_attachment_links() {
+        assert!(!is_attachment_link(
+            "https://example.com/attachments/banner.png"
+        ));
+        assert!(!is_attachment_link(
+            "https://cdn.discordapp.com/emojis/1.png"
+        ));
+        assert!(!is_attachment_link("not a link"));
+    }
+}
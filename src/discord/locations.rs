@@ -0,0 +1,243 @@
+use super::descriptions::MAX_FIELD_LEN;
+use super::event_guild_id;
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, ChannelType, Mentionable};
+use serenity::prelude::Mutex;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// Google Maps search, the location is added as the `query`
+const MAP_SEARCH_LINK: &str = "https://www.google.com/maps/search/";
+/// Online meeting platforms by domain, subdomains like `us02web.zoom.us` count too
+const MEETING_PLATFORMS: [(&str, &str); 4] = [
+    ("zoom.us", "Zoom"),
+    ("meet.google.com", "Google Meet"),
+    ("teams.microsoft.com", "Microsoft Teams"),
+    ("teams.live.com", "Microsoft Teams"),
+];
+/// Words of locations that are not a place on a map, like "Online, TBD" or "Room 5, Building A"
+const NOT_A_PLACE: [&str; 9] = [
+    "online", "tbd", "tba", "virtual", "remote", "discord", "room", "voice", "channel",
+];
+/// How long the voice channels of the event guild are remembered before looking them up again
+const VOICE_CHANNEL_CACHE_TIME: Duration = Duration::from_secs(5 * 60);
+
+lazy_static! {
+    /// Voice channels of the event guild by lowercase name, and when they were looked up
+    static ref VOICE_CHANNELS: Mutex<Option<(Instant, HashMap<String, ChannelId>)>> =
+        Mutex::new(None);
+}
+
+/// Renders an event location for its announcement
+///
+/// Links become clickable and labelled with their meeting platform, a location naming a voice
+/// channel of the event guild becomes a mention of it, and addresses get a map search link. Other
+/// locations, and any that would no longer fit in an embed field, are shown as they are.
+pub fn format_location(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    location: &str,
+) -> String {
+    let formatted = if location
+        .split_whitespace()
+        .any(|word| parse_link(word).is_some())
+    {
+        location
+            .split_whitespace()
+            .map(|word| match parse_link(word) {
+                Some(link) => format_link(messages, &link),
+                None => word.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    } else if let Some(channel) = voice_channel_mention(http, config, location) {
+        channel
+    } else if is_address(location) {
+        match Url::parse_with_params(MAP_SEARCH_LINK, &[("api", "1"), ("query", location)]) {
+            Ok(link) => messages.get(
+                "location_map",
+                &[("location", location), ("link", link.as_str())],
+            ),
+            Err(_) => location.to_string(),
+        }
+    } else {
+        location.to_string()
+    };
+
+    if formatted.chars().count() > MAX_FIELD_LEN {
+        location.to_string()
+    } else {
+        formatted
+    }
+}
+
+/// Mentions the voice channel of the event guild a location names, like `Game Night` or `#Game Night`
+///
+/// The channels are looked up at most every `VOICE_CHANNEL_CACHE_TIME`, since every announcement
+/// and edit renders its location.
+fn voice_channel_mention(http: &Http, config: &HypeBotConfig, location: &str) -> Option<String> {
+    let name = location.trim().trim_start_matches('#').trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut voice_channels = VOICE_CHANNELS.lock();
+    match &*voice_channels {
+        Some((looked_up, _)) if looked_up.elapsed() < VOICE_CHANNEL_CACHE_TIME => {}
+        _ => {
+            let guild_id = event_guild_id(http, config)?;
+            let channels = guild_id.channels(http).ok()?;
+
+            *voice_channels = Some((
+                Instant::now(),
+                channels
+                    .values()
+                    .filter(|channel| channel.kind == ChannelType::Voice)
+                    .map(|channel| (channel.name.to_lowercase(), channel.id))
+                    .collect(),
+            ));
+        }
+    }
+
+    voice_channels
+        .as_ref()?
+        .1
+        .get(&name.to_lowercase())
+        .map(|channel_id| channel_id.mention())
+}
+
+/// Parses a word of a location as a web link
+fn parse_link(word: &str) -> Option<Url> {
+    Url::parse(word)
+        .ok()
+        .filter(|link| link.scheme() == "http" || link.scheme() == "https")
+}
+
+/// Formats a link, labelled with its meeting platform or otherwise its site
+fn format_link(messages: &Messages, link: &Url) -> String {
+    let host = link.host_str().unwrap_or_default();
+    let platform = MEETING_PLATFORMS
+        .iter()
+        .find(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+        .map(|(_, platform)| *platform);
+
+    match platform {
+        Some(platform) => messages.get(
+            "location_online",
+            &[("platform", platform), ("link", link.as_str())],
+        ),
+        None => messages.get(
+            "location_link",
+            &[
+                ("site", host.trim_start_matches("www.")),
+                ("link", link.as_str()),
+            ],
+        ),
+    }
+}
+
+/// Guesses if a location is an address, starting with a street number like "12 Main St" or split
+/// by commas like "Central Park, New York"
+///
+/// Locations with words like "Online", "TBD" or "Room" are never addresses, so "Online, TBD" and
+/// "Room 5, Building A" are left alone.
+fn is_address(location: &str) -> bool {
+    let words: Vec<String> = location
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect();
+
+    if words
+        .iter()
+        .any(|word| NOT_A_PLACE.contains(&word.as_str()))
+    {
+        return false;
+    }
+
+    let street_number = words.len() >= 3
+        && words[0].starts_with(|c: char| c.is_ascii_digit())
+        && words[1..]
+            .iter()
+            .any(|word| word.chars().all(|c| c.is_alphabetic()));
+    let parts = location
+        .split(',')
+        .filter(|part| !part.trim().is_empty())
+        .count();
+
+    street_number || (parts >= 2 && words.len() >= 3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::MessageCatalog;
+
+    fn messages() -> Messages {
+        MessageCatalog::new(None).unwrap().messages("en")
+    }
+
+    fn link(link: &str) -> String {
+        format_link(&messages(), &parse_link(link).unwrap())
+    }
+
+    #[test]
+    fn street_addresses_are_addresses() {
+        assert!(is_address("12 Main St"));
+        assert!(is_address("221B Baker Street, London"));
+        assert!(is_address("1600 Pennsylvania Ave NW"));
+    }
+
+    #[test]
+    fn comma_separated_places_are_addresses() {
+        assert!(is_address("Central Park, New York"));
+        assert!(is_address("Library, 2nd floor, Springfield"));
+    }
+
+    #[test]
+    fn other_locations_are_not_addresses() {
+        assert!(!is_address("Room 5"));
+        assert!(!is_address("Online, TBD"));
+        assert!(!is_address("Room 5, Building A"));
+        assert!(!is_address("Online"));
+        assert!(!is_address("TBD"));
+        assert!(!is_address("Game Night"));
+        assert!(!is_address("Voice channel 2"));
+        assert!(!is_address("Gym, 7pm"));
+    }
+
+    #[test]
+    fn meeting_links_are_labelled_with_their_platform() {
+        assert_eq!(
+            link("https://us02web.zoom.us/j/123"),
+            "[Join on Zoom](https://us02web.zoom.us/j/123)"
+        );
+        assert_eq!(
+            link("https://meet.google.com/abc-defg-hij"),
+            "[Join on Google Meet](https://meet.google.com/abc-defg-hij)"
+        );
+    }
+
+    #[test]
+    fn other_links_are_labelled_with_their_site() {
+        assert_eq!(
+            link("https://www.example.com/event"),
+            "[example.com](https://www.example.com/event)"
+        );
+        assert_eq!(
+            link("https://notzoom.us/j/123"),
+            "[notzoom.us](https://notzoom.us/j/123)"
+        );
+    }
+
+    #[test]
+    fn only_web_links_are_parsed() {
+        assert!(parse_link("https://example.com").is_some());
+        assert!(parse_link("mailto:someone@example.com").is_none());
+        assert!(parse_link("Room").is_none());
+    }
+}
@@ -1,31 +1,96 @@
-use crate::database::models::{Event, NewEvent};
-use crate::database::{get_event_by_msg_id, remove_event, set_reminder};
+use crate::database::models::{Event, EventImage, NewEvent};
+use crate::database::{
+    get_event_by_id, get_event_by_msg_id, get_event_image, get_guild_settings,
+    get_user_preferences, insert_event, insert_event_image, insert_past_event, record_failed_dm,
+    remove_event, remove_event_image, set_event_role, set_published, set_reminder,
+};
 use crate::hypebot_config::HypeBotConfig;
-use crate::{INTERESTED_EMOJI, UNINTERESTED_EMOJI};
-use chrono::{DateTime, NaiveDateTime, Utc};
+use crate::messages::{MessageCatalog, Messages};
+use attendance::{record_attendance, schedule_check_in};
+use calendar::update_calendar;
+use channels::{parse_channel_id, remove_event_channels, schedule_event_channels};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use conflicts::{conflict_warning, find_conflicts};
+use delivery::{deliver_event_dms, get_reaction_users, queue_event_dm, EventDms};
+use descriptions::EmbedText;
+use discussion::{post_to_discussion, start_discussion};
+use images::{edit_with_image, with_uploaded_image};
+use locations::format_location;
+use reminders::{post_channel_reminder, reminder_channel, reminder_mode};
+use roles::{create_attendee_role, delete_attendee_role};
+use serenity::builder::CreateEmbed;
 use serenity::framework::standard::{CommandError, CommandResult};
 use serenity::http::Http;
-use serenity::model::prelude::{ChannelId, Message, Reaction, User};
+use serenity::model::prelude::{
+    Channel, ChannelId, GuildId, Mentionable, Message, MessageId, Reaction, ReactionType, User,
+};
 use serenity::prelude::TypeMapKey;
 use serenity::prelude::{Context, RwLock, ShareMap};
-use serenity::utils::Colour;
-use serenity::Result;
+use serenity::utils::{parse_emoji, Colour};
+use serenity::{Error, Result};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use strfmt::strfmt;
+use surveys::send_surveys;
+use tags::{event_tags, notify_subscribers, tag_color, with_auto_rsvp_users};
+use time::{discord_timestamp, format_time};
 use white_rabbit::{DateResult, Scheduler};
 
+pub mod attendance;
+pub mod calendar;
+pub mod channels;
+pub mod conflicts;
+pub mod delivery;
+pub mod descriptions;
+pub mod digest;
+pub mod discussion;
 pub mod events;
+pub mod images;
+pub mod locations;
+pub mod options;
+pub mod organizers;
+pub mod permissions;
+pub mod polls;
+pub mod preferences;
+pub mod proposals;
+pub mod reminders;
+pub mod roles;
+pub mod settings;
+pub mod surveys;
+pub mod tags;
+pub mod time;
+
+/// How long events last, they are cleaned up and check-in closes after this
+pub const EVENT_DURATION_MINUTES: i64 = 60;
+
+/// Number reactions from 1 to 10, used for ratings and votes
+pub const NUMBER_EMOJIS: [&str; 10] = [
+    "1\u{FE0F}\u{20E3}",
+    "2\u{FE0F}\u{20E3}",
+    "3\u{FE0F}\u{20E3}",
+    "4\u{FE0F}\u{20E3}",
+    "5\u{FE0F}\u{20E3}",
+    "6\u{FE0F}\u{20E3}",
+    "7\u{FE0F}\u{20E3}",
+    "8\u{FE0F}\u{20E3}",
+    "9\u{FE0F}\u{20E3}",
+    "\u{1F51F}",
+];
+
+/// Guild of the event channel, looked up once since the channel is fixed by the config
+static EVENT_GUILD_ID: AtomicU64 = AtomicU64::new(0);
 
 /// Struct for storing drafted events
 #[derive(Clone)]
 pub struct DraftEvent {
     pub event: NewEvent,
-    pub creator_id: u64,
+    pub image: Option<EventImage>,
 }
 
+/// Drafted events by the id of the user who drafted them
 impl TypeMapKey for DraftEvent {
-    type Value = DraftEvent;
+    type Value = HashMap<u64, DraftEvent>;
 }
 
 pub struct SchedulerKey;
@@ -34,8 +99,8 @@ impl TypeMapKey for SchedulerKey {
     type Value = Arc<RwLock<Scheduler>>;
 }
 
-/// Send a message to a reaction user
-pub fn send_message_to_reaction_users(ctx: &Context, reaction: &Reaction, msg_text: &str) {
+/// Send a message from the message catalog to a reaction user
+pub fn send_message_to_reaction_users(ctx: &Context, reaction: &Reaction, msg_key: &str) {
     if let Ok(config) = get_config(&ctx.data) {
         let db_link = config.db_url.clone();
         let message_id = reaction.message_id.0.to_string();
@@ -47,118 +112,557 @@ pub fn send_message_to_reaction_users(ctx: &Context, reaction: &Reaction, msg_te
             }
         };
 
+        let messages = match get_messages(&ctx.data, reaction.guild_id) {
+            Ok(messages) => messages,
+            Err(_) => return,
+        };
+
         let event_utc_time = DateTime::<Utc>::from_utc(event.event_time.clone(), Utc);
         let current_utc_time = chrono::offset::Utc::now();
 
+        let user = match reaction.user(&ctx.http) {
+            Ok(user) => user,
+            Err(_) => return,
+        };
+
+        if !wants_dm(&config, &user, Notification::Confirmation) {
+            return;
+        }
+
         let msg;
 
         if event_utc_time > current_utc_time {
-            // Format message
-            let mut fmt = HashMap::new();
-            fmt.insert("event".to_string(), event.event_name);
-            msg = strfmt(msg_text, &fmt).unwrap();
+            let tz = user_timezone(&config, &user);
+            msg = messages.get(
+                msg_key,
+                &[
+                    ("event", &event.event_name),
+                    ("time", &format_time(&messages, &tz, event.event_time)),
+                ],
+            );
         } else {
-            msg = format!("**{}** has already started!", &event.event_name)
+            msg = messages.get("event_started", &[("event", &event.event_name)]);
         }
 
-        if let Ok(user) = reaction.user(&ctx.http) {
-            send_dm_message(&ctx.http, user, &msg);
+        queue_event_dm(
+            &ctx.data,
+            ctx.http.clone(),
+            config,
+            messages,
+            event,
+            user,
+            msg,
+        );
+    }
+}
+
+/// Parses a configured emoji, either a unicode emoji or a custom guild emoji like `<:name:id>`
+pub fn parse_reaction_type(emoji: &str) -> ReactionType {
+    match parse_emoji(emoji) {
+        Some(emoji_identifier) => emoji_identifier.into(),
+        None => ReactionType::Unicode(emoji.to_string()),
+    }
+}
+
+/// Checks that an emoji given for an event can be reacted with
+///
+/// Custom emojis have to belong to the event guild, anything else has to look like a unicode emoji.
+/// `Err` is the catalog key of the message saying why it can not, which takes the `{emoji}`.
+pub fn check_event_emoji(
+    ctx: &Context,
+    config: &HypeBotConfig,
+    emoji: &str,
+) -> std::result::Result<(), &'static str> {
+    let emoji_identifier = match parse_emoji(emoji) {
+        Some(emoji_identifier) => emoji_identifier,
+        None if is_unicode_emoji(emoji) => return Ok(()),
+        None => return Err("emoji_invalid"),
+    };
+
+    let guild_id = event_guild_id(&ctx.http, config).ok_or("emoji_no_server")?;
+    let in_guild = match guild_id.to_guild_cached(&ctx.cache) {
+        Some(guild) => guild.read().emojis.contains_key(&emoji_identifier.id),
+        None => guild_id
+            .to_partial_guild(&ctx.http)
+            .map(|guild| guild.emojis.contains_key(&emoji_identifier.id))
+            .unwrap_or(false),
+    };
+
+    if in_guild {
+        Ok(())
+    } else {
+        Err("emoji_other_server")
+    }
+}
+
+/// Guesses if text is a single unicode emoji, rather than a word or a broken custom emoji
+fn is_unicode_emoji(emoji: &str) -> bool {
+    // Keycaps like 1️⃣ start with an ASCII character, but no emoji has letters or spaces
+    !emoji.is_ascii()
+        && !emoji
+            .chars()
+            .any(|c| c.is_ascii_alphabetic() || c.is_whitespace() || c == '<' || c == ':')
+        && emoji.chars().count() <= 10
+}
+
+/// Checks if a reaction matches a configured emoji
+pub fn emoji_matches(reaction: &ReactionType, emoji: &str) -> bool {
+    match (reaction, parse_reaction_type(emoji)) {
+        (ReactionType::Custom { id, .. }, ReactionType::Custom { id: emoji_id, .. }) => {
+            *id == emoji_id
+        }
+        (ReactionType::Unicode(unicode), ReactionType::Unicode(emoji)) => *unicode == emoji,
+        _ => false,
+    }
+}
+
+/// Gets the emoji to react with to receive reminders for an event, falling back to the config
+pub fn interested_emoji(config: &HypeBotConfig, event_emoji: &Option<String>) -> String {
+    event_emoji
+        .clone()
+        .unwrap_or_else(|| config.interested_emoji.clone())
+}
+
+/// Gets the emoji to react with when not interested in an event, falling back to the config
+pub fn uninterested_emoji(config: &HypeBotConfig, event_emoji: &Option<String>) -> String {
+    event_emoji
+        .clone()
+        .unwrap_or_else(|| config.uninterested_emoji.clone())
+}
+
+/// Checks if a reaction is the interested emoji of an event in the event channel
+pub fn is_interested_reaction(config: &HypeBotConfig, reaction: &Reaction) -> bool {
+    if reaction.channel_id.0 != config.event_channel {
+        return false;
+    }
+
+    match get_event_by_msg_id(config.db_url.clone(), reaction.message_id.0.to_string()) {
+        Ok(event) => emoji_matches(
+            &reaction.emoji,
+            &interested_emoji(config, &event.interested_emoji),
+        ),
+        Err(_) => false,
+    }
+}
+
+/// Gets the timezone a user has chosen, or the event timezone if they have not chosen one
+pub fn user_timezone(config: &HypeBotConfig, user: &User) -> Tz {
+    get_user_preferences(config.db_url.clone(), user.id.0.to_string())
+        .ok()
+        .and_then(|preferences| preferences.timezone)
+        .and_then(|timezone| timezone.parse::<Tz>().ok())
+        .unwrap_or(config.event_timezone)
+}
+
+/// Kinds of DMs a user can turn off
+#[derive(Clone, Copy)]
+pub enum Notification {
+    /// Sent when reacting to an event
+    Confirmation,
+    /// Sent when an event is about to start
+    Reminder,
+    /// Sent after an event with a survey
+    Survey,
+    /// Anything else, like cancellations, only turned off by opting out of DMs entirely
+    Update,
+}
+
+/// Checks if a user wants to receive a kind of DM, users without preferences receive everything
+pub fn wants_dm(config: &HypeBotConfig, user: &User, notification: Notification) -> bool {
+    match get_user_preferences(config.db_url.clone(), user.id.0.to_string()) {
+        Ok(preferences) => {
+            preferences.dms
+                && match notification {
+                    Notification::Confirmation => preferences.confirmation_dms,
+                    Notification::Reminder => preferences.reminder_dms,
+                    Notification::Survey => preferences.survey_dms,
+                    Notification::Update => true,
+                }
+        }
+        Err(_) => true,
+    }
+}
+
+/// Send a DM message to a user, returning whether it could be delivered
+pub fn send_dm_message(http: &Http, user: User, message: &str) -> bool {
+    match user.create_dm_channel(http) {
+        Ok(dm_channel) => dm_channel
+            .send_message(http, |m| m.content(message))
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Records a DM about an event that could not be delivered, pinging the user in the fallback
+/// channel the first time
+///
+/// The ping only asks the user to open their DMs, what the DM said is not shown in the channel.
+pub fn record_event_dm_failure(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+    user: &User,
+) {
+    match record_failed_dm(config.db_url.clone(), event.id, user.id.0.to_string()) {
+        Ok(true) => {
+            if let Some(fallback_channel) = config.dm_fallback_channel {
+                let fallback_msg = messages.get("dm_fallback", &[("user", &user.mention())]);
+                ChannelId(fallback_channel).say(http, fallback_msg).ok();
+            }
         }
+        Ok(false) => {}
+        Err(e) => error!("Unable to record failed DM: {}", e),
     }
 }
 
-/// Send a DM message to a user
-pub fn send_dm_message(http: &Http, user: User, message: &String) {
-    if let Ok(dm_channel) = user.create_dm_channel(&http) {
-        dm_channel.send_message(&http, |m| m.content(message)).ok();
+/// Fills in the description of an event's announcement, with its time and how to get reminders
+pub fn event_description(config: &HypeBotConfig, messages: &Messages, event: &NewEvent) -> String {
+    messages.get(
+        "event_description",
+        &[
+            (
+                "time",
+                &format_time(messages, &config.event_timezone, event.event_time),
+            ),
+            ("timestamp", &discord_timestamp(event.event_time, 'F')),
+            ("relative", &discord_timestamp(event.event_time, 'R')),
+            ("description", &event.event_desc),
+            ("emoji", &interested_emoji(config, &event.interested_emoji)),
+        ],
+    )
+}
+
+/// Gathers the text of an event's announcement, with its location as rendered by `format_location`
+pub fn event_embed_text(
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &NewEvent,
+    location: &str,
+) -> EmbedText {
+    let mut fields = vec![
+        (
+            "location",
+            messages.get("event_location", &[]),
+            location.to_string(),
+            true,
+        ),
+        (
+            "organizer",
+            messages.get("event_organizer", &[]),
+            event.organizer.clone(),
+            true,
+        ),
+    ];
+
+    if let Some(link) = &event.discussion_link {
+        fields.push((
+            "discussion",
+            messages.get("event_discussion", &[]),
+            format!("[{}]({})", messages.get("discussion_link", &[]), link),
+            false,
+        ));
+    }
+
+    let tags = event_tags(&event.tags);
+    if !tags.is_empty() {
+        fields.push((
+            "tags",
+            messages.get("event_tags", &[]),
+            tags.join(", "),
+            false,
+        ));
+    }
+
+    let channels: Vec<String> = [&event.voice_channel_id, &event.text_channel_id]
+        .iter()
+        .filter_map(|channel_id| parse_channel_id(channel_id))
+        .map(|channel_id| channel_id.mention())
+        .collect();
+
+    if !channels.is_empty() {
+        fields.push((
+            "channels",
+            messages.get("event_channels", &[]),
+            channels.join(" "),
+            false,
+        ));
+    }
+
+    EmbedText {
+        title: event.event_name.clone(),
+        description: event_description(config, messages, event),
+        footer: messages.get("event_footer", &[]),
+        fields,
     }
 }
 
+/// Fills in the announcement embed for an event, with its location as rendered by `format_location`
+pub fn build_event_embed<'a>(
+    e: &'a mut CreateEmbed,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &NewEvent,
+    location: &str,
+) -> &'a mut CreateEmbed {
+    let utc_time = DateTime::<Utc>::from_utc(event.event_time.clone(), Utc);
+
+    // Anything too long for Discord is shortened, the preview warns about it
+    let EmbedText {
+        title,
+        description,
+        footer,
+        fields,
+    } = event_embed_text(config, messages, event, location).fit();
+
+    e.title(title)
+        .color(tag_color(config, &event.tags).unwrap_or(Colour::PURPLE))
+        .description(description)
+        .thumbnail(event.thumbnail_link.clone())
+        .footer(|f| f.text(footer))
+        .timestamp(utc_time.to_rfc3339());
+
+    if let Some(image) = &event.image_link {
+        e.image(image);
+    }
+
+    for (_, name, value, inline) in fields {
+        e.field(name, value, inline);
+    }
+
+    e
+}
+
 /// Sends the event message to the event channel
+///
+/// An attached image is uploaded with the message, so it does not depend on where it came from.
 pub fn send_event_msg(
     http: &Http,
     config: &HypeBotConfig,
+    messages: &Messages,
     channel_id: u64,
     event: &NewEvent,
+    image: Option<&EventImage>,
     react: bool,
 ) -> Result<Message> {
     let channel = http.get_channel(channel_id)?;
-
-    let utc_time = DateTime::<Utc>::from_utc(event.event_time.clone(), Utc);
-
-    let native_time = utc_time.with_timezone(&config.event_timezone);
+    let location = format_location(http, config, messages, &event.event_loc);
 
     // Send message
-    let msg = channel.id().send_message(&http, |m| {
-        m.embed(|e| {
-            e.title(event.event_name.clone())
-                .color(Colour::PURPLE)
-                .description(format!(
-                    "**{}**\n{}\n\nReact with {} below to receive event reminders!",
-                    native_time.format("%A, %B %d @ %I:%M %P %t %Z"),
-                    event.event_desc,
-                    INTERESTED_EMOJI
-                ))
-                .thumbnail(event.thumbnail_link.clone())
-                .footer(|f| f.text("Local Event Time"))
-                .timestamp(utc_time.to_rfc3339())
-                .field("Location", &event.event_loc, true)
-                .field("Organizer", &event.organizer, true)
-        })
-    })?;
+    let msg = match image {
+        Some(image) => {
+            let event = with_uploaded_image(event, image);
+            channel.id().send_files(
+                http,
+                vec![(image.data.as_slice(), image.filename.as_str())],
+                |m| m.embed(|e| build_event_embed(e, config, messages, &event, &location)),
+            )?
+        }
+        None => channel.id().send_message(http, |m| {
+            m.embed(|e| build_event_embed(e, config, messages, event, &location))
+        })?,
+    };
 
     if react {
-        // Add reacts
-        msg.react(http, INTERESTED_EMOJI)?;
-        msg.react(http, UNINTERESTED_EMOJI)?;
+        // Add reacts, an announcement that can not be reacted to is no use so it is removed
+        let reacted = msg
+            .react(
+                http,
+                parse_reaction_type(&interested_emoji(config, &event.interested_emoji)),
+            )
+            .and_then(|_| {
+                msg.react(
+                    http,
+                    parse_reaction_type(&uninterested_emoji(config, &event.uninterested_emoji)),
+                )
+            });
+
+        if let Err(e) = reacted {
+            msg.delete(http).ok();
+            return Err(e);
+        }
     }
 
     Ok(msg)
 }
 
-/// Updates the draft event stored in the context data
-pub fn update_draft_event(
+/// Updates the announcement message of an already posted event
+///
+/// An image uploaded with the announcement stays attached to it, so the embed points at it again.
+pub fn edit_event_msg(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+) -> Result<Message> {
+    let message_id = event
+        .message_id
+        .parse::<u64>()
+        .map_err(|_| Error::Other("Event has no announcement message"))?;
+    let mut new_event: NewEvent = event.clone().into();
+    if let Ok(image) = get_event_image(config.db_url.clone(), event.id) {
+        new_event = with_uploaded_image(&new_event, &image);
+    }
+    let location = format_location(http, config, messages, &new_event.event_loc);
+
+    ChannelId(config.event_channel).edit_message(http, message_id, |m| {
+        m.embed(|e| build_event_embed(e, config, messages, &new_event, &location))
+    })
+}
+
+/// Updates the announcement message of an already posted event, uploading a new image for it
+pub fn replace_event_image(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+    image: &EventImage,
+) -> Result<()> {
+    let message_id = event
+        .message_id
+        .parse::<u64>()
+        .map_err(|_| Error::Other("Event has no announcement message"))?;
+    let new_event = with_uploaded_image(&event.clone().into(), image);
+    let location = format_location(http, config, messages, &new_event.event_loc);
+
+    let mut embed = CreateEmbed::default();
+    build_event_embed(&mut embed, config, messages, &new_event, &location);
+
+    edit_with_image(
+        http,
+        ChannelId(config.event_channel),
+        message_id,
+        embed,
+        image,
+    )
+}
+
+/// Posts an event to the event channel, stores it, and schedules its reminders
+pub fn publish_event(
     ctx: &Context,
-    event_name: String,
-    event_desc: String,
-    organizer: String,
-    location: String,
-    thumbnail: String,
-    event_time: NaiveDateTime,
+    new_event: &NewEvent,
+    image: Option<&EventImage>,
+) -> std::result::Result<Event, CommandError> {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, event_guild_id(&ctx.http, &config))?;
+    let mut new_event = new_event.clone();
+
+    // Send event message
+    let event_msg = send_event_msg(
+        &ctx.http,
+        &config,
+        &messages,
+        config.event_channel,
+        &new_event,
+        image,
+        true,
+    )?;
+
+    new_event.message_id = event_msg.id.0.to_string();
+
+    // Without its row the announcement would never be updated or cleaned up
+    let mut event = match insert_event(config.db_url.clone(), &new_event) {
+        Ok(event) => event,
+        Err(e) => {
+            event_msg.delete(&ctx.http).ok();
+            return Err(e.into());
+        }
+    };
+
+    // Kept to show the image again whenever the announcement is edited
+    if let Some(image) = image {
+        let mut image = image.clone();
+        image.event_id = event.id;
+        if let Err(e) = insert_event_image(config.db_url.clone(), &image) {
+            error!("Unable to store the image of '{}': {}", event.event_name, e);
+        }
+    }
+
+    if event.attendee_role {
+        event.role_id = create_attendee_role(&ctx.http, &config, &messages, &event.event_name);
+        if let Err(e) = set_event_role(config.db_url.clone(), event.id, event.role_id.clone()) {
+            error!(
+                "Unable to store the attendee role of '{}': {}",
+                event.event_name, e
+            );
+        }
+    }
+
+    if let Err(e) = start_discussion(&ctx.http, &config, &messages, &event) {
+        error!(
+            "Unable to start discussion for '{}': {}",
+            event.event_name, e.0
+        );
+    }
+
+    schedule_event(&ctx.http, &ctx.data, &event);
+    update_calendar(&ctx.http, &ctx.data);
+    notify_subscribers(&ctx.data, &ctx.http, &config, &messages, &event);
+
+    Ok(event)
+}
+
+/// Updates a user's draft event stored in the context data
+pub fn update_draft_event(
+    data: &Arc<RwLock<ShareMap>>,
+    mut event: NewEvent,
+    image: Option<EventImage>,
     creator_id: u64,
 ) -> CommandResult {
-    let mut data = ctx.data.write();
-    let mut draft_event = data
+    let mut data = data.write();
+    let draft_events = data
         .get_mut::<DraftEvent>()
         .ok_or(CommandError("Unable get draft event!".to_string()))?;
 
-    draft_event.event.event_name = event_name;
-    draft_event.event.event_desc = event_desc;
-    draft_event.event.event_loc = location;
-    draft_event.event.organizer = organizer;
-    draft_event.event.thumbnail_link = thumbnail;
-    draft_event.event.message_id = String::new();
-    draft_event.event.event_time = event_time;
-    draft_event.creator_id = creator_id;
+    event.message_id = String::new();
+    event.creator_id = creator_id.to_string();
+    draft_events.insert(creator_id, DraftEvent { event, image });
     Ok(())
 }
 
-/// Sends the draft event stored in the context data
-pub fn send_draft_event(ctx: &Context, channel: ChannelId) -> CommandResult {
-    let data = ctx.data.read();
-    let config = data
-        .get::<HypeBotConfig>()
-        .ok_or(CommandError("Config not found!".to_string()))?;
-    let draft_event = data
-        .get::<DraftEvent>()
-        .ok_or(CommandError("Draft event not found!".to_string()))?;
-
-    channel.send_message(&ctx, |m| {
-        m.content(format!(
-            "Draft message, use the `confirm` command to post it."
-        ))
-    })?;
-    send_event_msg(&ctx.http, config, channel.0, &draft_event.event, false)?;
+/// Sends a user's draft event stored in the context data, warning about anything shortened to fit
+/// and any events it overlaps
+pub fn send_draft_event(
+    http: &Http,
+    data: &Arc<RwLock<ShareMap>>,
+    channel: ChannelId,
+    creator_id: u64,
+) -> CommandResult {
+    let config = get_config(data)?;
+    let draft_event = get_draft_event(data, creator_id)?
+        .ok_or(CommandError("Unable to get draft event".to_string()))?;
+    let messages = get_messages(data, event_guild_id(http, &config))?;
+
+    channel.send_message(http, |m| m.content(messages.get("draft_preview", &[])))?;
+    send_event_msg(
+        http,
+        &config,
+        &messages,
+        channel.0,
+        &draft_event.event,
+        draft_event.image.as_ref(),
+        false,
+    )?;
+
+    let location = format_location(http, &config, &messages, &draft_event.event.event_loc);
+    let truncated: Vec<String> =
+        event_embed_text(&config, &messages, &draft_event.event, &location)
+            .truncated_parts()
+            .iter()
+            .map(|part| messages.get(&format!("field_{}", part), &[]))
+            .collect();
+    if !truncated.is_empty() {
+        channel.say(
+            http,
+            messages.get("truncated_warning", &[("parts", &truncated.join(", "))]),
+        )?;
+    }
+
+    let conflicts = find_conflicts(&config, draft_event.event.event_time, None);
+    if let Some(warning) = conflict_warning(&config, &messages, &draft_event.event, &conflicts) {
+        channel.say(http, warning)?;
+    }
+
     Ok(())
 }
 
@@ -174,16 +678,54 @@ pub fn get_config(
     Ok(config.clone())
 }
 
-/// Gets the draft event from context data
+/// Gets the messages in the language selected for a guild, or the configured language
+pub fn get_messages(
+    data: &Arc<RwLock<ShareMap>>,
+    guild_id: Option<GuildId>,
+) -> std::result::Result<Messages, CommandError> {
+    let config = get_config(data)?;
+
+    let language = guild_id
+        .and_then(|guild_id| get_guild_settings(config.db_url.clone(), guild_id.0.to_string()).ok())
+        .and_then(|settings| settings.language)
+        .unwrap_or(config.language.clone());
+
+    let data_read = data.read();
+    let catalog = data_read
+        .get::<MessageCatalog>()
+        .ok_or(CommandError("Unable to get message catalog".to_string()))?;
+
+    Ok(catalog.messages(&language))
+}
+
+/// Gets the guild the event channel is in
+pub fn event_guild_id(http: &Http, config: &HypeBotConfig) -> Option<GuildId> {
+    let cached = EVENT_GUILD_ID.load(Ordering::Relaxed);
+    if cached != 0 {
+        return Some(GuildId(cached));
+    }
+
+    match http.get_channel(config.event_channel) {
+        Ok(Channel::Guild(channel)) => {
+            let guild_id = channel.read().guild_id;
+            EVENT_GUILD_ID.store(guild_id.0, Ordering::Relaxed);
+            Some(guild_id)
+        }
+        _ => None,
+    }
+}
+
+/// Gets a user's draft event from context data, `None` if they have not drafted one
 pub fn get_draft_event(
     data: &Arc<RwLock<ShareMap>>,
-) -> std::result::Result<DraftEvent, CommandError> {
+    creator_id: u64,
+) -> std::result::Result<Option<DraftEvent>, CommandError> {
     let data_read = data.read();
-    let draft_event = data_read
+    let draft_events = data_read
         .get::<DraftEvent>()
         .ok_or(CommandError("Unable to queued event".to_string()))?;
 
-    Ok(draft_event.clone())
+    Ok(draft_events.get(&creator_id).cloned())
 }
 
 /// Get the scheduler
@@ -210,29 +752,6 @@ pub fn log_error(
     };
 }
 
-/// Checks if the user has permission to use this bot
-pub fn permission_check(ctx: &mut Context, msg: &Message, _command_name: &str) -> bool {
-    if let Some(guild_id) = msg.guild_id {
-        if let Ok(config) = get_config(&ctx.data) {
-            if let Ok(roles) = ctx.http.get_guild_roles(guild_id.0) {
-                for role in roles {
-                    if config.event_roles.contains(&role.id.0) {
-                        let has_role = match msg.author.has_role(&ctx, guild_id, role) {
-                            Ok(has_role) => has_role,
-                            Err(_) => false,
-                        };
-                        if has_role {
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    false
-}
-
 pub fn schedule_event(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Event) {
     let scheduler = {
         let mut context = data.write();
@@ -252,28 +771,165 @@ pub fn schedule_event(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Ev
 
         scheduler.add_task_datetime(reminder_time, move |_| send_reminders(&http, &data, &event));
     }
+
+    schedule_event_channels(http, data, event);
+    schedule_check_in(http, data, event);
+}
+
+/// Schedules an event waiting to be posted to be posted at its publish time
+pub fn schedule_publish(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Event) {
+    if let Some(publish_time) = event.publish_time {
+        let scheduler = get_scheduler(data).unwrap();
+        let mut scheduler = scheduler.write();
+        let publish_time = DateTime::<Utc>::from_utc(publish_time, Utc);
+        let http = http.clone();
+        let data = data.clone();
+        let event = event.clone();
+
+        scheduler.add_task_datetime(publish_time, move |_| {
+            if let Err(e) = publish_pending_event(&http, &data, &event) {
+                error!("Unable to post event '{}': {}", event.event_name, e.0);
+            }
+            DateResult::Done
+        });
+    }
+}
+
+/// Posts an event that was waiting to be posted and schedules its reminders
+pub fn publish_pending_event(
+    http: &Arc<Http>,
+    data: &Arc<RwLock<ShareMap>>,
+    event: &Event,
+) -> std::result::Result<(), CommandError> {
+    let config = get_config(data)?;
+
+    // Skip events that have been canceled or already posted
+    let event = match get_event_by_id(config.db_url.clone(), event.id) {
+        Ok(event) if event.publish_time.is_some() => event,
+        _ => return Ok(()),
+    };
+
+    // An event whose time passed while it was held back, e.g. while the bot was offline, is dropped
+    if Utc::now().naive_utc() > event.event_time {
+        warn!(
+            "Dropping pending event {}, its time passed before it was posted",
+            event.event_name
+        );
+        remove_event(config.db_url.clone(), event.id)?;
+        remove_event_image(config.db_url.clone(), event.id).ok();
+        return Ok(());
+    }
+
+    let messages = get_messages(data, event_guild_id(http, &config))?;
+    let image = get_event_image(config.db_url.clone(), event.id).ok();
+    let event_msg = send_event_msg(
+        http,
+        &config,
+        &messages,
+        config.event_channel,
+        &event.clone().into(),
+        image.as_ref(),
+        true,
+    )?;
+
+    // Still pending, so it would be posted again on the next start
+    if let Err(e) = set_published(config.db_url.clone(), event.id, event_msg.id.0.to_string()) {
+        event_msg.delete(http).ok();
+        return Err(e.into());
+    }
+
+    // Once posted the event is always scheduled, anything else failing is only logged
+    if event.attendee_role {
+        let role_id = create_attendee_role(http, &config, &messages, &event.event_name);
+        if let Err(e) = set_event_role(config.db_url.clone(), event.id, role_id) {
+            error!(
+                "Unable to store the attendee role of '{}': {}",
+                event.event_name, e
+            );
+        }
+    }
+
+    let event = get_event_by_id(config.db_url.clone(), event.id).unwrap_or(event);
+    if let Err(e) = start_discussion(http, &config, &messages, &event) {
+        error!(
+            "Unable to start discussion for '{}': {}",
+            event.event_name, e.0
+        );
+    }
+
+    let event = get_event_by_id(config.db_url.clone(), event.id).unwrap_or(event);
+
+    schedule_event(http, data, &event);
+    update_calendar(http, data);
+    notify_subscribers(data, http, &config, &messages, &event);
+
+    Ok(())
 }
 
 /// Send reminders
 pub fn send_reminders(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Event) -> DateResult {
     let config = get_config(&data).unwrap();
+
+    // Skip events that have been canceled or rescheduled since this reminder was scheduled
+    let event = match get_event_by_id(config.db_url.clone(), event.id) {
+        Ok(current_event) if current_event.event_time == event.event_time => current_event,
+        _ => return DateResult::Done,
+    };
     let event_channel_id = config.event_channel;
     let event_time: DateTime<Utc> = DateTime::<Utc>::from_utc(event.event_time.clone(), Utc);
-    let delete_time = event_time + chrono::Duration::minutes(60);
+    let delete_time = event_time + chrono::Duration::minutes(EVENT_DURATION_MINUTES);
 
     if let Ok(message_id) = event.message_id.parse::<u64>() {
         // Get message id
-        if let Ok(message) = http.get_message(event_channel_id, message_id) {
-            let reaction_users = message
-                .reaction_users(&http, INTERESTED_EMOJI, None, None)
-                .unwrap_or(Vec::<User>::new());
-
-            // Build reminder message
-            let msg: String = format!("Hello! **{}** is starting soon!", &event.event_name);
+        let reaction_users = get_reaction_users(
+            http,
+            ChannelId(event_channel_id),
+            MessageId(message_id),
+            parse_reaction_type(&interested_emoji(&config, &event.interested_emoji)),
+        );
+        let reaction_users = with_auto_rsvp_users(http, &config, &event, reaction_users);
+
+        let guild_id = event_guild_id(http, &config);
+        let mode = reminder_mode(&config, &event, guild_id);
+
+        if let Ok(messages) = get_messages(data, guild_id) {
+            let reminder = messages.get(
+                "event_reminder",
+                &[
+                    ("event", &event.event_name),
+                    (
+                        "time",
+                        &format_time(&messages, &config.event_timezone, event.event_time),
+                    ),
+                ],
+            );
+            post_to_discussion(http, &config, &messages, &event, &reminder);
+
+            if mode.posts_in_channel() {
+                post_channel_reminder(
+                    http,
+                    &config,
+                    &messages,
+                    &event,
+                    reminder_channel(&config, guild_id),
+                    &reaction_users,
+                );
+            }
 
-            // Send reminder to each reacted user
-            for user in reaction_users {
-                send_dm_message(&http, user, &msg);
+            // Send reminder to each reacted user, in their own timezone
+            if mode.sends_dms() {
+                deliver_event_dms(
+                    data,
+                    http.clone(),
+                    EventDms {
+                        config: config.clone(),
+                        messages,
+                        event: event.clone(),
+                        users: reaction_users,
+                        msg_key: "event_reminder",
+                        notification: Notification::Reminder,
+                    },
+                );
             }
         }
 
@@ -284,7 +940,6 @@ pub fn send_reminders(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Ev
 
         let http = http.clone();
         let data = data.clone();
-        let event = event.clone();
 
         scheduler.add_task_datetime(delete_time, move |_| {
             delete_event(&http, &data, &event);
@@ -299,8 +954,47 @@ pub fn send_reminders(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Ev
 pub fn delete_event(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, event: &Event) {
     let config = get_config(&data).unwrap();
 
+    // Skip events that have been rescheduled since the cleanup was scheduled, the stored event also
+    // has anything created since, like its channels
+    let event = match get_event_by_id(config.db_url.clone(), event.id) {
+        Ok(current_event) if current_event.event_time != event.event_time => return,
+        Ok(current_event) => current_event,
+        Err(_) => event.clone(),
+    };
+
+    let attendance = record_attendance(http, &config, &event);
+
+    if event.survey {
+        // Survey those who checked in, or everyone interested if nobody checked in
+        let checked_in = attendance.iter().any(|record| record.attended);
+        let attendees = attendance
+            .into_iter()
+            .filter(|record| record.attended || !checked_in)
+            .map(|record| record.user_id)
+            .collect();
+
+        if let Ok(messages) = get_messages(data, event_guild_id(http, &config)) {
+            send_surveys(
+                data,
+                http.clone(),
+                config.clone(),
+                messages,
+                event.clone(),
+                attendees,
+            );
+        }
+    }
+
+    // Keep what is needed to run the event again with `clone`
+    if let Err(e) = insert_past_event(config.db_url.clone(), &event.clone().into()) {
+        error!("Unable to store past event {}: {}", event.event_name, e);
+    }
+
     remove_event(config.db_url.clone(), event.id).ok();
     if let Ok(message_id) = event.message_id.parse::<u64>() {
         http.delete_message(config.event_channel, message_id).ok();
     }
+    delete_attendee_role(http, &config, &event);
+    remove_event_channels(http, &config, &event);
+    update_calendar(http, data);
 }
@@ -0,0 +1,115 @@
+use serenity::framework::standard::{Args, Delimiter};
+use std::collections::HashMap;
+
+/// Splits `key:value` options out of a command's arguments
+///
+/// Only the given keys are treated as options, so links like `http://...` are left alone. Values
+/// can be quoted, e.g. `when:"next saturday 7pm"`. Returns the remaining arguments and the options.
+pub fn take_options(args: &Args, keys: &[&str]) -> (Args, HashMap<String, String>) {
+    let mut options = HashMap::new();
+    let mut remaining = Vec::new();
+
+    for token in split_tokens(args.message()) {
+        let option = token.find(':').and_then(|index| {
+            let key = token[..index].to_lowercase();
+            if keys.contains(&key.as_str()) {
+                Some((key, token[index + 1..].replace("\"", "")))
+            } else {
+                None
+            }
+        });
+
+        match option {
+            Some((key, value)) => {
+                options.insert(key, value);
+            }
+            None => remaining.push(token),
+        }
+    }
+
+    let args = Args::new(&remaining.join(" "), &[Delimiter::Single(' ')]);
+
+    (args, options)
+}
+
+/// Parses a yes/no option value
+pub fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "yes" | "true" | "on" => Some(true),
+        "no" | "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Splits a message on whitespace, keeping quoted sections together
+fn split_tokens(message: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut quoted = false;
+
+    for c in message.chars() {
+        if c == '"' {
+            quoted = !quoted;
+            token.push(c);
+        } else if c.is_whitespace() && !quoted {
+            if !token.is_empty() {
+                tokens.push(token);
+                token = String::new();
+            }
+        } else {
+            token.push(c);
+        }
+    }
+
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(message: &str, keys: &[&str]) -> (String, HashMap<String, String>) {
+        let (args, options) = take_options(&Args::new(message, &[Delimiter::Single(' ')]), keys);
+        (args.message().to_string(), options)
+    }
+
+    #[test]
+    fn take_options_splits_out_known_keys() {
+        let (rest, options) = options("\"Game Night\" role:yes Survey:no", &["role", "survey"]);
+
+        assert_eq!(rest, "\"Game Night\"");
+        assert_eq!(options.get("role").map(String::as_str), Some("yes"));
+        assert_eq!(options.get("survey").map(String::as_str), Some("no"));
+    }
+
+    #[test]
+    fn take_options_keeps_quoted_values_together() {
+        let (rest, options) = options("\"Game Night\" when:\"next saturday 7pm\"", &["when"]);
+
+        assert_eq!(rest, "\"Game Night\"");
+        assert_eq!(
+            options.get("when").map(String::as_str),
+            Some("next saturday 7pm")
+        );
+    }
+
+    #[test]
+    fn take_options_leaves_links_and_unknown_keys() {
+        let (rest, options) = options("https://example.com/image.png note:hi", &["role"]);
+
+        assert_eq!(rest, "https://example.com/image.png note:hi");
+        assert!(options.is_empty());
+    }
+
+    #[test]
+    fn parse_flag_accepts_yes_and_no() {
+        assert_eq!(parse_flag("Yes"), Some(true));
+        assert_eq!(parse_flag("on"), Some(true));
+        assert_eq!(parse_flag("false"), Some(false));
+        assert_eq!(parse_flag("maybe"), None);
+    }
+}
@@ -0,0 +1,317 @@
+use super::delivery::queue_dm;
+use super::permissions::{can_manage_event, has_permission, Permission};
+use super::{edit_event_msg, get_config, get_messages, wants_dm, Notification};
+use crate::database::models::{Event, NewEvent};
+use crate::database::{
+    add_co_organizer, get_co_organizers, get_event_by_id, get_event_by_msg_id, get_event_by_name,
+    get_failed_dms, remove_co_organizer, set_event_creator, update_event,
+};
+use serenity::framework::standard::{macros::command, Args, CommandError, CommandResult};
+use serenity::model::prelude::{Mentionable, Message, Reaction, User};
+use serenity::prelude::Context;
+
+#[command]
+/// Adds a co-organizer to an event
+///
+/// `~addorganizer "event name" @user`
+///
+/// **Note**
+/// Co-organizers can edit and cancel the event, and are sent a DM when someone RSVPs. Only the
+/// organizer or an admin can add them.
+fn addorganizer(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let (event, user) = match parse_organizer_args(ctx, msg, &mut args, true)? {
+        Some(parsed) => parsed,
+        None => return Ok(()),
+    };
+
+    if is_organizer(
+        &event,
+        &user,
+        &get_co_organizers(config.db_url.clone(), event.id)?,
+    ) {
+        msg.reply(
+            &ctx,
+            messages.get(
+                "organizer_already",
+                &[("user", &user.mention()), ("event", &event.event_name)],
+            ),
+        )?;
+        return Ok(());
+    }
+
+    add_co_organizer(config.db_url.clone(), event.id, user.id.0.to_string())?;
+
+    msg.reply(
+        &ctx,
+        messages.get(
+            "organizer_added",
+            &[("user", &user.mention()), ("event", &event.event_name)],
+        ),
+    )?;
+
+    Ok(())
+}
+
+#[command]
+/// Removes a co-organizer from an event
+///
+/// `~removeorganizer "event name" @user`
+///
+/// **Note**
+/// Only the organizer or an admin can remove co-organizers.
+fn removeorganizer(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let (event, user) = match parse_organizer_args(ctx, msg, &mut args, true)? {
+        Some(parsed) => parsed,
+        None => return Ok(()),
+    };
+
+    if remove_co_organizer(config.db_url.clone(), event.id, user.id.0.to_string())? == 0 {
+        msg.reply(
+            &ctx,
+            messages.get(
+                "organizer_not_co",
+                &[("user", &user.mention()), ("event", &event.event_name)],
+            ),
+        )?;
+        return Ok(());
+    }
+
+    msg.reply(
+        &ctx,
+        messages.get(
+            "organizer_removed",
+            &[("user", &user.mention()), ("event", &event.event_name)],
+        ),
+    )?;
+
+    Ok(())
+}
+
+#[command]
+/// Transfers ownership of an event to another user
+///
+/// `~transfer "event name" @user`
+///
+/// **Note**
+/// Only the organizer or an admin can transfer an event, for example when the organizer has left the server.
+fn transfer(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let (event, user) = match parse_organizer_args(ctx, msg, &mut args, true)? {
+        Some(parsed) => parsed,
+        None => return Ok(()),
+    };
+
+    // The new organizer should not also be listed as a co-organizer
+    remove_co_organizer(config.db_url.clone(), event.id, user.id.0.to_string())?;
+    set_event_creator(config.db_url.clone(), event.id, user.id.0.to_string())?;
+
+    // Show the new organizer in the announcement if it named the old one
+    let mut new_event: NewEvent = event.clone().into();
+    new_event.organizer = replace_mention(&event.organizer, &event.creator_id, &user.mention());
+    if new_event.organizer != event.organizer {
+        update_event(config.db_url.clone(), event.id, &new_event)?;
+
+        let event = get_event_by_id(config.db_url.clone(), event.id)?;
+        if event.publish_time.is_none() {
+            edit_event_msg(&ctx.http, &config, &messages, &event)?;
+        }
+    }
+
+    msg.reply(
+        &ctx,
+        messages.get(
+            "organizer_transferred",
+            &[("user", &user.mention()), ("event", &event.event_name)],
+        ),
+    )?;
+
+    Ok(())
+}
+
+#[command]
+/// Lists the users interested in an event who could not be sent a DM
+///
+/// `~unreachable "event name"`
+///
+/// **Note**
+/// Usually these users have DMs from server members turned off.
+fn unreachable(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+    let event = match get_event_by_name(config.db_url.clone(), event_name) {
+        Ok(event) => event,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("event_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    if !can_manage_event(ctx, guild_id, &msg.author, &event, Permission::Edit) {
+        msg.reply(
+            &ctx,
+            messages.get("unreachable_denied", &[("event", &event.event_name)]),
+        )?;
+        return Ok(());
+    }
+
+    let users: Vec<String> = get_failed_dms(config.db_url.clone(), event.id)?
+        .iter()
+        .filter_map(|user_id| user_id.parse::<u64>().ok())
+        .filter_map(|user_id| ctx.http.get_user(user_id).ok())
+        .map(|user| user.tag())
+        .collect();
+
+    if users.is_empty() {
+        msg.reply(
+            &ctx,
+            messages.get("unreachable_none", &[("event", &event.event_name)]),
+        )?;
+    } else {
+        msg.reply(
+            &ctx,
+            messages.get(
+                "unreachable_users",
+                &[("event", &event.event_name), ("users", &users.join(", "))],
+            ),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Parses the event name and mentioned user, checking that the author can manage the event
+///
+/// If `owner_only` is set, co-organizers are not allowed, only the organizer or an admin.
+fn parse_organizer_args(
+    ctx: &Context,
+    msg: &Message,
+    args: &mut Args,
+    owner_only: bool,
+) -> Result<Option<(Event, User)>, CommandError> {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    // Parse args
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+    let user = match msg.mentions.first() {
+        Some(user) => user.clone(),
+        None => {
+            msg.reply(ctx, messages.get("no_user_mentioned", &[]))?;
+            return Ok(None);
+        }
+    };
+
+    let event = match get_event_by_name(config.db_url.clone(), event_name) {
+        Ok(event) => event,
+        Err(_) => {
+            msg.reply(ctx, messages.get("event_not_found", &[]))?;
+            return Ok(None);
+        }
+    };
+
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(None),
+    };
+
+    let allowed = if owner_only {
+        event.creator_id == msg.author.id.0.to_string()
+            || has_permission(ctx, guild_id, &msg.author, Permission::Admin)
+    } else {
+        can_manage_event(ctx, guild_id, &msg.author, &event, Permission::Edit)
+    };
+
+    if !allowed {
+        msg.reply(
+            ctx,
+            messages.get("organizers_denied", &[("event", &event.event_name)]),
+        )?;
+        return Ok(None);
+    }
+
+    Ok(Some((event, user)))
+}
+
+/// Replaces mentions of a user in text, in both the `<@id>` and `<@!id>` forms
+fn replace_mention(text: &str, user_id: &str, mention: &str) -> String {
+    text.replace(&format!("<@!{}>", user_id), mention)
+        .replace(&format!("<@{}>", user_id), mention)
+}
+
+/// Checks if a user is the organizer or a co-organizer of an event
+pub fn is_organizer(event: &Event, user: &User, co_organizers: &[String]) -> bool {
+    let user_id = user.id.0.to_string();
+
+    event.creator_id == user_id || co_organizers.contains(&user_id)
+}
+
+/// Lets the organizers of an event know someone has changed their RSVP
+pub fn notify_organizers(ctx: &Context, reaction: &Reaction, interested: bool) {
+    let config = match get_config(&ctx.data) {
+        Ok(config) => config,
+        Err(_) => return,
+    };
+
+    let event = match get_event_by_msg_id(config.db_url.clone(), reaction.message_id.0.to_string())
+    {
+        Ok(event) => event,
+        Err(_) => return,
+    };
+
+    let user = match reaction.user(&ctx.http) {
+        Ok(user) => user,
+        Err(_) => return,
+    };
+
+    if user.bot {
+        return;
+    }
+
+    let messages = match get_messages(&ctx.data, reaction.guild_id) {
+        Ok(messages) => messages,
+        Err(_) => return,
+    };
+
+    let msg_key = if interested {
+        "rsvp_interested"
+    } else {
+        "rsvp_uninterested"
+    };
+    let msg = messages.get(
+        msg_key,
+        &[("user", &user.tag()), ("event", &event.event_name)],
+    );
+
+    let mut organizers = get_co_organizers(config.db_url.clone(), event.id).unwrap_or_default();
+    organizers.push(event.creator_id.clone());
+
+    // Organizers reacting to their own event do not need to hear about it
+    organizers.retain(|organizer_id| *organizer_id != user.id.0.to_string());
+
+    for organizer_id in organizers {
+        if let Ok(organizer_id) = organizer_id.parse::<u64>() {
+            if let Ok(organizer) = ctx.http.get_user(organizer_id) {
+                if !wants_dm(&config, &organizer, Notification::Update) {
+                    continue;
+                }
+
+                queue_dm(&ctx.data, ctx.http.clone(), organizer, msg.clone());
+            }
+        }
+    }
+}
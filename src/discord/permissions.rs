@@ -0,0 +1,243 @@
+use super::organizers::is_organizer;
+use super::{get_config, get_messages};
+use crate::database::get_co_organizers;
+use crate::database::models::Event;
+use crate::hypebot_config::HypeBotConfig;
+use serenity::model::prelude::{GuildId, Message, RoleId, User};
+use serenity::prelude::Context;
+
+/// Actions that are restricted to certain roles
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    /// Create, confirm, and moderate events
+    Create,
+    /// Edit any event
+    Edit,
+    /// Cancel any event
+    Cancel,
+    /// Do anything
+    Admin,
+}
+
+impl Permission {
+    /// Gets the permission required to run a command, `None` if anyone can run it
+    pub fn for_command(command_name: &str) -> Option<Permission> {
+        match command_name {
+            "propose" | "timezone" | "notifications" | "checkin" | "subscribe" | "unsubscribe" => {
+                None
+            }
+            // Organizers can always manage their own events, checked by the command
+            "edit" | "cancel" | "addorganizer" | "removeorganizer" | "transfer" | "unreachable" => {
+                None
+            }
+            "language" | "reminders" | "conflicts" => Some(Permission::Admin),
+            _ => Some(Permission::Create),
+        }
+    }
+
+    /// Roles granted this permission by the config
+    fn roles<'a>(&self, config: &'a HypeBotConfig) -> &'a Vec<u64> {
+        match self {
+            Permission::Create => &config.event_roles,
+            Permission::Edit => &config.edit_roles,
+            Permission::Cancel => &config.cancel_roles,
+            Permission::Admin => &config.admin_roles,
+        }
+    }
+
+    /// Checks if any of a member's roles grant this permission, admin roles grant every permission
+    fn granted_by(&self, config: &HypeBotConfig, member_roles: &[RoleId]) -> bool {
+        member_roles.iter().any(|role| {
+            self.roles(config).contains(&role.0)
+                || Permission::Admin.roles(config).contains(&role.0)
+        })
+    }
+
+    /// Catalog key of the description of the action
+    pub fn action_key(&self) -> &'static str {
+        match self {
+            Permission::Create => "permission_create",
+            Permission::Edit => "permission_edit",
+            Permission::Cancel => "permission_cancel",
+            Permission::Admin => "permission_admin",
+        }
+    }
+}
+
+/// Checks if the user has permission to use a command, letting them know if they do not
+pub fn permission_check(ctx: &mut Context, msg: &Message, command_name: &str) -> bool {
+    let permission = match Permission::for_command(command_name) {
+        Some(permission) => permission,
+        None => return true,
+    };
+
+    if let Some(guild_id) = msg.guild_id {
+        if has_permission(ctx, guild_id, &msg.author, permission) {
+            return true;
+        }
+
+        if let Ok(messages) = get_messages(&ctx.data, msg.guild_id) {
+            let action = messages.get(permission.action_key(), &[]);
+            msg.reply(
+                &ctx,
+                messages.get("permission_denied", &[("action", &action)]),
+            )
+            .ok();
+        }
+    }
+
+    false
+}
+
+/// Checks if a user has a role granting a permission, admins have every permission
+pub fn has_permission(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: &User,
+    permission: Permission,
+) -> bool {
+    let config = match get_config(&ctx.data) {
+        Ok(config) => config,
+        Err(_) => return false,
+    };
+
+    let member = match guild_id.member(ctx, user.id) {
+        Ok(member) => member,
+        Err(_) => return false,
+    };
+
+    permission.granted_by(&config, &member.roles)
+}
+
+/// Names the roles granting a permission, for telling users who can do something
+///
+/// Roles missing from the cache are left out.
+pub fn permission_role_names(
+    ctx: &Context,
+    guild_id: GuildId,
+    permission: Permission,
+) -> Vec<String> {
+    let config = match get_config(&ctx.data) {
+        Ok(config) => config,
+        Err(_) => return Vec::new(),
+    };
+
+    let guild = match guild_id.to_guild_cached(&ctx.cache) {
+        Some(guild) => guild,
+        None => return Vec::new(),
+    };
+    let guild = guild.read();
+
+    permission
+        .roles(&config)
+        .iter()
+        .chain(Permission::Admin.roles(&config).iter())
+        .filter_map(|role_id| guild.roles.get(&RoleId(*role_id)))
+        .map(|role| format!("**{}**", role.name))
+        .collect()
+}
+
+/// Checks if a user can edit or cancel an event, organizers and co-organizers can always manage
+/// their own events
+pub fn can_manage_event(
+    ctx: &Context,
+    guild_id: GuildId,
+    user: &User,
+    event: &Event,
+    permission: Permission,
+) -> bool {
+    let co_organizers = match get_config(&ctx.data) {
+        Ok(config) => get_co_organizers(config.db_url.clone(), event.id).unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+
+    is_organizer(event, user, &co_organizers) || has_permission(ctx, guild_id, user, permission)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::{Config, File, FileFormat};
+
+    fn config() -> HypeBotConfig {
+        let mut cfg = Config::new();
+        cfg.merge(File::from_str(
+            r#"
+            db_url = "mysql://localhost/hypebot"
+            default_thumbnail_link = "https://example.com/thumbnail.png"
+            discord_key = "key"
+            prefix = "~"
+            event_channel = 1
+            event_roles = [10]
+            edit_roles = [20]
+            cancel_roles = [30]
+            admin_roles = [40]
+            event_timezone = "America/New_York"
+            log_path = "hypebot.log"
+            "#,
+            FileFormat::Toml,
+        ))
+        .unwrap();
+
+        cfg.try_into().unwrap()
+    }
+
+    #[test]
+    fn for_command_lets_anyone_use_member_commands() {
+        for command in &[
+            "propose",
+            "timezone",
+            "notifications",
+            "checkin",
+            "subscribe",
+        ] {
+            assert_eq!(Permission::for_command(command), None);
+        }
+    }
+
+    #[test]
+    fn for_command_leaves_event_management_to_the_command() {
+        for command in &["edit", "cancel", "addorganizer", "transfer", "unreachable"] {
+            assert_eq!(Permission::for_command(command), None);
+        }
+    }
+
+    #[test]
+    fn for_command_restricts_settings_to_admins() {
+        for command in &["language", "reminders", "conflicts"] {
+            assert_eq!(Permission::for_command(command), Some(Permission::Admin));
+        }
+    }
+
+    #[test]
+    fn for_command_requires_create_by_default() {
+        for command in &["create", "confirm", "approve", "poll", "stats"] {
+            assert_eq!(Permission::for_command(command), Some(Permission::Create));
+        }
+    }
+
+    #[test]
+    fn granted_by_only_grants_the_role_permission() {
+        let config = config();
+
+        assert!(Permission::Create.granted_by(&config, &[RoleId(10)]));
+        assert!(!Permission::Edit.granted_by(&config, &[RoleId(10)]));
+        assert!(Permission::Edit.granted_by(&config, &[RoleId(5), RoleId(20)]));
+        assert!(!Permission::Admin.granted_by(&config, &[RoleId(20), RoleId(30)]));
+        assert!(!Permission::Cancel.granted_by(&config, &[]));
+    }
+
+    #[test]
+    fn granted_by_admin_grants_every_permission() {
+        let config = config();
+
+        for permission in &[
+            Permission::Create,
+            Permission::Edit,
+            Permission::Cancel,
+            Permission::Admin,
+        ] {
+            assert!(permission.granted_by(&config, &[RoleId(40)]));
+        }
+    }
+}
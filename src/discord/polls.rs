@@ -0,0 +1,345 @@
+use super::delivery::get_reaction_users;
+use super::options::take_options;
+use super::time::{format_time, parse_event_time};
+use super::{
+    event_guild_id, get_config, get_messages, get_scheduler, parse_reaction_type, send_draft_event,
+    update_draft_event, NUMBER_EMOJIS,
+};
+use crate::database::models::{NewEvent, NewPoll, Poll};
+use crate::database::{get_poll_by_id, get_poll_options, insert_poll, remove_poll};
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serenity::framework::standard::{macros::command, Args, CommandError, CommandResult};
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, Mentionable, Message, MessageId, UserId};
+use serenity::prelude::{Context, RwLock, ShareMap};
+use serenity::utils::{content_safe, ContentSafeOptions};
+use std::sync::Arc;
+use white_rabbit::DateResult;
+
+/// Hours voting stays open if no closing time is given
+const DEFAULT_POLL_HOURS: i64 = 24;
+/// Filled into the draft for details the poll did not give
+const UNDECIDED: &str = "TBD";
+
+#[command]
+/// Starts a poll to pick the time of an event
+///
+/// `~poll "event name" "friday 7pm" "saturday 2pm" "sunday 11am"`
+///
+/// **Times**
+/// Each time uses the same format as `create` and gets a number to vote with, up to 10 times
+///
+/// **Closing**
+/// `closes:"thursday 5pm"` sets when voting closes, defaults to 24 hours after the poll is posted.
+/// The time with the most votes is then previewed as a draft for you to `confirm`. Voting has to
+/// close before the earliest time
+///
+/// **Description and location**
+/// `description:"..."` and `location:"..."` are filled into the draft, otherwise they are left as
+/// TBD and can be changed with `edit` once the event is posted
+fn poll(ctx: &mut Context, msg: &Message, args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let (mut args, options) = take_options(&args, &["closes", "description", "location"]);
+
+    let event_name = match args.single_quoted::<String>() {
+        Ok(event_name) => event_name.replace("\"", ""),
+        Err(_) => {
+            msg.reply(&ctx, messages.get("no_event_name", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let now = Utc::now().naive_utc();
+    let mut option_times = Vec::new();
+    while let Ok(time_string) = args.single_quoted::<String>() {
+        let option_time =
+            match parse_event_time(&config.event_timezone, &time_string.replace("\"", "")) {
+                Some(option_time) => option_time,
+                None => {
+                    msg.reply(&ctx, messages.get("time_format_help", &[]))?;
+                    return Ok(());
+                }
+            };
+
+        if now > option_time {
+            msg.reply(
+                &ctx,
+                messages.get(
+                    "poll_time_passed",
+                    &[("time", &time_string.replace("\"", ""))],
+                ),
+            )?;
+            return Ok(());
+        }
+
+        option_times.push(option_time);
+    }
+
+    if option_times.len() < 2 || option_times.len() > NUMBER_EMOJIS.len() {
+        msg.reply(
+            &ctx,
+            messages.get(
+                "poll_option_count",
+                &[("max", &NUMBER_EMOJIS.len().to_string())],
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let closes_time = match options.get("closes") {
+        Some(closes) => match parse_event_time(&config.event_timezone, closes) {
+            Some(closes_time) => closes_time,
+            None => {
+                msg.reply(&ctx, messages.get("time_format_help", &[]))?;
+                return Ok(());
+            }
+        },
+        None => now + Duration::hours(DEFAULT_POLL_HOURS),
+    };
+
+    if now > closes_time {
+        msg.reply(&ctx, messages.get("poll_closes_passed", &[]))?;
+        return Ok(());
+    }
+
+    if option_times
+        .iter()
+        .any(|option_time| closes_time >= *option_time)
+    {
+        msg.reply(&ctx, messages.get("poll_closes_late", &[]))?;
+        return Ok(());
+    }
+
+    // Clean channel, role, and everyone pings
+    let settings = ContentSafeOptions::default()
+        .clean_role(true)
+        .clean_here(true)
+        .clean_user(false)
+        .clean_everyone(true);
+
+    let event_name = content_safe(&ctx.cache, event_name, &settings);
+    let event_desc = options
+        .get("description")
+        .map(|desc| content_safe(&ctx.cache, desc, &settings));
+    let event_loc = options
+        .get("location")
+        .map(|loc| content_safe(&ctx.cache, loc, &settings));
+
+    let option_lines: Vec<String> = option_times
+        .iter()
+        .zip(NUMBER_EMOJIS.iter())
+        .map(|(option_time, emoji)| {
+            format!(
+                "{} {}",
+                emoji,
+                format_time(&messages, &config.event_timezone, *option_time)
+            )
+        })
+        .collect();
+
+    let poll_msg = msg.channel_id.say(
+        &ctx.http,
+        messages.get(
+            "poll_start",
+            &[
+                ("event", &event_name),
+                (
+                    "closes",
+                    &format_time(&messages, &config.event_timezone, closes_time),
+                ),
+                ("options", &option_lines.join("\n")),
+            ],
+        ),
+    )?;
+
+    // A poll that can not be voted on or tallied is removed rather than left in the channel
+    for emoji in NUMBER_EMOJIS.iter().take(option_times.len()) {
+        if let Err(e) = poll_msg.react(&ctx.http, parse_reaction_type(emoji)) {
+            poll_msg.delete(&ctx.http).ok();
+            return Err(e.into());
+        }
+    }
+
+    let poll = match insert_poll(
+        config.db_url.clone(),
+        &NewPoll {
+            event_name,
+            event_desc,
+            event_loc,
+            creator_id: msg.author.id.0.to_string(),
+            channel_id: msg.channel_id.0.to_string(),
+            message_id: poll_msg.id.0.to_string(),
+            closes_time,
+        },
+        &option_times,
+    ) {
+        Ok(poll) => poll,
+        Err(e) => {
+            poll_msg.delete(&ctx.http).ok();
+            return Err(e.into());
+        }
+    };
+
+    schedule_poll(&ctx.http, &ctx.data, &poll);
+
+    Ok(())
+}
+
+/// Schedules a poll to be tallied when voting closes
+pub fn schedule_poll(http: &Arc<Http>, data: &Arc<RwLock<ShareMap>>, poll: &Poll) {
+    let scheduler = get_scheduler(data).unwrap();
+    let mut scheduler = scheduler.write();
+    let closes_time = DateTime::<Utc>::from_utc(poll.closes_time, Utc);
+    let http = http.clone();
+    let data = data.clone();
+    let poll_id = poll.id;
+
+    scheduler.add_task_datetime(closes_time, move |_| {
+        if let Err(e) = close_poll(&http, &data, poll_id) {
+            error!("Unable to close poll {}: {}", poll_id, e.0);
+        }
+        DateResult::Done
+    });
+}
+
+/// Picks the winning time and its votes from a poll's tally
+///
+/// The time with the most votes that has not passed yet wins, ties go to the earliest time.
+fn poll_winner(
+    tally: Vec<(NaiveDateTime, usize)>,
+    now: NaiveDateTime,
+) -> Option<(NaiveDateTime, usize)> {
+    tally
+        .into_iter()
+        .filter(|(option_time, votes)| *option_time > now && *votes > 0)
+        .max_by(|(a_time, a_votes), (b_time, b_votes)| {
+            a_votes.cmp(b_votes).then(b_time.cmp(a_time))
+        })
+}
+
+/// Tallies the votes of a poll and previews the winning time as its creator's draft
+fn close_poll(
+    http: &Arc<Http>,
+    data: &Arc<RwLock<ShareMap>>,
+    poll_id: i32,
+) -> Result<(), CommandError> {
+    let config = get_config(data)?;
+
+    // Skip polls that have already been tallied
+    let poll = match get_poll_by_id(config.db_url.clone(), poll_id) {
+        Ok(poll) => poll,
+        Err(_) => return Ok(()),
+    };
+    let options = get_poll_options(config.db_url.clone(), poll.id)?;
+    remove_poll(config.db_url.clone(), poll.id)?;
+
+    let channel_id = ChannelId(poll.channel_id.parse::<u64>()?);
+    let message_id = MessageId(poll.message_id.parse::<u64>()?);
+    let creator_id = poll.creator_id.parse::<u64>()?;
+    let messages = get_messages(data, event_guild_id(http, &config))?;
+
+    let now = Utc::now().naive_utc();
+    let tally: Vec<(NaiveDateTime, usize)> = options
+        .into_iter()
+        .filter(|(_, option_time)| *option_time > now)
+        .filter_map(|(position, option_time)| {
+            let emoji = NUMBER_EMOJIS.get(position as usize)?;
+            let votes =
+                get_reaction_users(http, channel_id, message_id, parse_reaction_type(emoji)).len();
+            Some((option_time, votes))
+        })
+        .collect();
+
+    let (event_time, votes) = match poll_winner(tally, now) {
+        Some(winner) => winner,
+        None => {
+            channel_id.say(
+                http,
+                messages.get("poll_no_votes", &[("event", &poll.event_name)]),
+            )?;
+            return Ok(());
+        }
+    };
+
+    let organizer = UserId(creator_id).mention();
+    let draft = NewEvent {
+        event_name: poll.event_name.clone(),
+        event_desc: poll.event_desc.unwrap_or_else(|| UNDECIDED.to_string()),
+        event_loc: poll.event_loc.unwrap_or_else(|| UNDECIDED.to_string()),
+        organizer: organizer.clone(),
+        event_time,
+        message_id: String::new(),
+        thumbnail_link: config.default_thumbnail_link.clone(),
+        reminder_sent: 0,
+        creator_id: poll.creator_id.clone(),
+        publish_time: None,
+        interested_emoji: None,
+        uninterested_emoji: None,
+        reminder_mode: None,
+        attendee_role: false,
+        role_id: None,
+        channels_lead: None,
+        voice_channel_id: None,
+        text_channel_id: None,
+        discussion_link: None,
+        survey: false,
+        tags: None,
+        image_link: None,
+    };
+
+    channel_id.say(
+        http,
+        messages.get(
+            "poll_closed",
+            &[
+                ("event", &poll.event_name),
+                (
+                    "time",
+                    &format_time(&messages, &config.event_timezone, event_time),
+                ),
+                ("votes", &votes.to_string()),
+                ("organizer", &organizer),
+            ],
+        ),
+    )?;
+
+    update_draft_event(data, draft, None, creator_id)?;
+    send_draft_event(http, data, channel_id, creator_id)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2020, 11, 7).and_hms(hour, 0, 0)
+    }
+
+    #[test]
+    fn most_votes_wins() {
+        let tally = vec![(at(18), 1), (at(19), 3), (at(20), 2)];
+        assert_eq!(poll_winner(tally, at(12)), Some((at(19), 3)));
+    }
+
+    #[test]
+    fn ties_go_to_earliest_time() {
+        let tally = vec![(at(20), 2), (at(18), 2), (at(19), 1)];
+        assert_eq!(poll_winner(tally, at(12)), Some((at(18), 2)));
+    }
+
+    #[test]
+    fn passed_times_are_skipped() {
+        let tally = vec![(at(10), 5), (at(18), 1)];
+        assert_eq!(poll_winner(tally, at(12)), Some((at(18), 1)));
+    }
+
+    #[test]
+    fn no_votes_has_no_winner() {
+        let tally = vec![(at(10), 4), (at(18), 0), (at(19), 0)];
+        assert_eq!(poll_winner(tally, at(12)), None);
+    }
+}
@@ -0,0 +1,158 @@
+use super::{get_config, get_messages, user_timezone};
+use crate::database::models::UserPreferences;
+use crate::database::{get_user_preferences, set_user_notifications, set_user_timezone};
+use crate::messages::Messages;
+use chrono_tz::Tz;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::model::prelude::Message;
+use serenity::prelude::Context;
+
+#[command]
+/// Sets the timezone event times are shown in when the bot DMs you
+///
+/// `~timezone Europe/Berlin`
+///
+/// **Timezones**
+/// Supported timezones can be found at https://docs.rs/chrono-tz/0.5.1/chrono_tz/#modules. Run
+/// `~timezone` without a timezone to see your current one, or `~timezone reset` to go back to the
+/// server's timezone.
+fn timezone(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let timezone = match args.single::<String>() {
+        Ok(timezone) => timezone,
+        Err(_) => {
+            msg.reply(
+                &ctx,
+                messages.get(
+                    "timezone_current",
+                    &[("timezone", user_timezone(&config, &msg.author).name())],
+                ),
+            )?;
+            return Ok(());
+        }
+    };
+
+    if timezone.to_lowercase() == "reset" {
+        set_user_timezone(config.db_url.clone(), msg.author.id.0.to_string(), None)?;
+        msg.reply(
+            &ctx,
+            messages.get(
+                "timezone_reset",
+                &[("timezone", config.event_timezone.name())],
+            ),
+        )?;
+        return Ok(());
+    }
+
+    let tz = match timezone.parse::<Tz>() {
+        Ok(tz) => tz,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("timezone_unknown", &[]))?;
+            return Ok(());
+        }
+    };
+
+    set_user_timezone(
+        config.db_url.clone(),
+        msg.author.id.0.to_string(),
+        Some(tz.name().to_string()),
+    )?;
+
+    msg.reply(
+        &ctx,
+        messages.get("timezone_set", &[("timezone", tz.name())]),
+    )?;
+
+    Ok(())
+}
+
+#[command]
+/// Chooses which DMs the bot sends you
+///
+/// `~notifications reminders off`
+///
+/// **Notifications**
+/// `confirmations`: DMs when you react to an event
+/// `reminders`: DMs when an event you reacted to is about to start
+/// `digest`: a weekly DM listing upcoming events, off unless you turn it on
+/// `surveys`: DMs asking for feedback after events with a survey
+/// `all`: every DM, `~notifications all off` means the bot never DMs you
+///
+/// Run `~notifications` on its own to see your current settings.
+fn notifications(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let user_id = msg.author.id.0.to_string();
+
+    let kind = match args.single::<String>() {
+        Ok(kind) => kind.to_lowercase(),
+        Err(_) => {
+            let preferences = get_user_preferences(config.db_url.clone(), user_id.clone())
+                .unwrap_or_else(|_| UserPreferences::new(user_id));
+            msg.reply(&ctx, describe_notifications(&messages, &preferences))?;
+            return Ok(());
+        }
+    };
+
+    let enabled = match args.single::<String>().map(|value| value.to_lowercase()) {
+        Ok(ref value) if value == "on" => true,
+        Ok(ref value) if value == "off" => false,
+        _ => {
+            msg.reply(&ctx, messages.get("notifications_toggle", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let (confirmations, reminders, digest, surveys, all) = match kind.as_str() {
+        "confirmations" => (Some(enabled), None, None, None, None),
+        "reminders" => (None, Some(enabled), None, None, None),
+        "digest" => (None, None, Some(enabled), None, None),
+        "surveys" => (None, None, None, Some(enabled), None),
+        "all" => (None, None, None, None, Some(enabled)),
+        _ => {
+            msg.reply(&ctx, messages.get("notifications_unknown", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let preferences = set_user_notifications(
+        config.db_url.clone(),
+        user_id,
+        confirmations,
+        reminders,
+        digest,
+        surveys,
+        all,
+    )?;
+
+    msg.reply(&ctx, describe_notifications(&messages, &preferences))?;
+
+    Ok(())
+}
+
+/// Summarizes which DMs a user receives
+fn describe_notifications(messages: &Messages, preferences: &UserPreferences) -> String {
+    if !preferences.dms {
+        return messages.get("notifications_none", &[]);
+    }
+
+    let toggle = |enabled: bool| {
+        if enabled {
+            messages.get("notification_on", &[])
+        } else {
+            messages.get("notification_off", &[])
+        }
+    };
+
+    messages.get(
+        "notifications_summary",
+        &[
+            ("confirmations", &toggle(preferences.confirmation_dms)),
+            ("reminders", &toggle(preferences.reminder_dms)),
+            ("digest", &toggle(preferences.digest_dms)),
+            ("surveys", &toggle(preferences.survey_dms)),
+        ],
+    )
+}
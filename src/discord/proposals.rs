@@ -0,0 +1,404 @@
+use super::conflicts::{blocks_conflicts, conflict_warning, find_conflicts};
+use super::delivery::queue_dm;
+use super::descriptions::{is_text_file, take_code_block};
+use super::events::{parse_event_args, EVENT_OPTIONS};
+use super::options::take_options;
+use super::permissions::{has_permission, Permission};
+use super::{
+    emoji_matches, event_guild_id, get_config, get_messages, parse_reaction_type, publish_event,
+    send_event_msg, wants_dm, Notification,
+};
+use crate::database::models::{NewEvent, NewProposal, Proposal};
+use crate::database::{
+    get_proposal_by_msg_id, get_proposal_by_name, insert_proposal, proposal_exists, remove_proposal,
+};
+use crate::messages::Messages;
+use chrono::Utc;
+use serenity::framework::standard::{macros::command, Args, CommandError, CommandResult};
+use serenity::model::prelude::{Mentionable, Message, Reaction, User};
+use serenity::prelude::{Context, Mutex};
+use std::collections::HashSet;
+
+lazy_static! {
+    /// Proposals a moderator is approving or rejecting right now
+    static ref CLOSING_PROPOSALS: Mutex<HashSet<i32>> = Mutex::new(HashSet::new());
+}
+
+#[command]
+/// Proposes an event for review by the event organizers
+///
+/// `~propose "event name" "04:20pm 2069-04-20" "event description" "http://optional.thumbnail.link" "optional organizer"`
+///
+/// **Note**
+/// Takes the same arguments as `create`, but not its options or an image, the organizers can add
+/// those once it is approved. The proposal is sent to the moderators, you will receive a DM once it
+/// has been approved or rejected.
+fn propose(ctx: &mut Context, msg: &Message, args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let moderation_channel = match config.moderation_channel {
+        Some(moderation_channel) => moderation_channel,
+        None => {
+            msg.reply(&ctx, messages.get("proposals_disabled", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let (args, description) = take_code_block(&args);
+    let (mut args, options) = take_options(&args, &EVENT_OPTIONS);
+
+    // Proposals only keep the event's arguments, options and images would be lost on approval
+    if !options.is_empty()
+        || msg
+            .attachments
+            .iter()
+            .any(|attachment| !is_text_file(&attachment.filename))
+    {
+        msg.reply(&ctx, messages.get("proposal_options", &[]))?;
+        return Ok(());
+    }
+
+    let new_event = match parse_event_args(ctx, msg, &mut args, description)? {
+        Some(new_event) => new_event,
+        None => return Ok(()),
+    };
+
+    // Send the proposal to the moderators, with the instructions on the same message so they are
+    // removed together when the proposal is closed
+    let mut proposal_msg = send_event_msg(
+        &ctx.http,
+        &config,
+        &messages,
+        moderation_channel,
+        &new_event,
+        None,
+        false,
+    )?;
+    proposal_msg.edit(&ctx, |m| {
+        m.content(messages.get(
+            "proposal_moderation",
+            &[
+                ("user", &msg.author.mention()),
+                ("approve", &config.approve_emoji),
+                ("reject", &config.reject_emoji),
+            ],
+        ))
+    })?;
+    proposal_msg.react(&ctx, parse_reaction_type(&config.approve_emoji))?;
+    proposal_msg.react(&ctx, parse_reaction_type(&config.reject_emoji))?;
+
+    let new_proposal = NewProposal {
+        event_name: new_event.event_name.clone(),
+        event_desc: new_event.event_desc.clone(),
+        event_loc: new_event.event_loc.clone(),
+        organizer: new_event.organizer.clone(),
+        event_time: new_event.event_time,
+        thumbnail_link: new_event.thumbnail_link.clone(),
+        proposer_id: msg.author.id.0.to_string(),
+        message_id: proposal_msg.id.0.to_string(),
+    };
+
+    // Without its row the proposal could never be approved or rejected
+    if let Err(e) = insert_proposal(config.db_url.clone(), &new_proposal) {
+        proposal_msg.delete(&ctx).ok();
+        return Err(e.into());
+    }
+
+    msg.reply(&ctx, messages.get("proposal_sent", &[]))?;
+
+    Ok(())
+}
+
+#[command]
+/// Approves a proposed event and posts it
+///
+/// `~approve "event name"`
+fn approve(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    // Parse args
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+
+    let proposal = match get_proposal_by_name(config.db_url.clone(), event_name) {
+        Ok(proposal) => proposal,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("proposal_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    match approve_proposal(ctx, &proposal, &msg.author)? {
+        Some(problem) => msg.reply(&ctx, problem)?,
+        None => msg.reply(
+            &ctx,
+            messages.get("proposal_approve_done", &[("event", &proposal.event_name)]),
+        )?,
+    };
+
+    Ok(())
+}
+
+#[command]
+/// Rejects a proposed event
+///
+/// `~reject "event name" "optional reason"`
+///
+/// **Reason**
+/// The reason is optional, if one is provided it is sent to the user who proposed the event
+fn reject(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    // Parse args
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+    let reason = args
+        .single_quoted::<String>()
+        .ok()
+        .map(|reason| reason.replace("\"", ""));
+
+    let proposal = match get_proposal_by_name(config.db_url.clone(), event_name) {
+        Ok(proposal) => proposal,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("proposal_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    match reject_proposal(ctx, &proposal, &msg.author, reason)? {
+        Some(problem) => msg.reply(&ctx, problem)?,
+        None => msg.reply(
+            &ctx,
+            messages.get("proposal_reject_done", &[("event", &proposal.event_name)]),
+        )?,
+    };
+
+    Ok(())
+}
+
+/// Handles approve and reject reactions on proposals in the moderation channel
+pub fn moderation_reaction(ctx: &Context, reaction: &Reaction) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+
+    if config.moderation_channel != Some(reaction.channel_id.0) {
+        return Ok(());
+    }
+
+    let approved = emoji_matches(&reaction.emoji, &config.approve_emoji);
+    if !approved && !emoji_matches(&reaction.emoji, &config.reject_emoji) {
+        return Ok(());
+    }
+
+    let proposal =
+        match get_proposal_by_msg_id(config.db_url.clone(), reaction.message_id.0.to_string()) {
+            Ok(proposal) => proposal,
+            Err(_) => return Ok(()),
+        };
+
+    let moderator = reaction.user(&ctx.http)?;
+    if moderator.bot {
+        return Ok(());
+    }
+
+    // Only members with an event role can moderate proposals
+    let guild_id = match reaction.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+    if !has_permission(ctx, guild_id, &moderator, Permission::Create) {
+        return Ok(());
+    }
+
+    let problem = if approved {
+        approve_proposal(ctx, &proposal, &moderator)?
+    } else {
+        reject_proposal(ctx, &proposal, &moderator, None)?
+    };
+
+    if let Some(problem) = problem {
+        reaction
+            .channel_id
+            .say(&ctx.http, format!("{} {}", moderator.mention(), problem))?;
+    }
+
+    Ok(())
+}
+
+/// Publishes a proposal as an event and lets the proposer know
+///
+/// Returns why the proposal could not be approved, if it could not. The proposal is claimed before
+/// it is published, so moderators closing it at the same time can not post it twice.
+fn approve_proposal(
+    ctx: &Context,
+    proposal: &Proposal,
+    moderator: &User,
+) -> Result<Option<String>, CommandError> {
+    let config = get_config(&ctx.data)?;
+    let messages = get_proposal_messages(ctx)?;
+    let new_event: NewEvent = proposal.clone().into();
+
+    if Utc::now().naive_utc() > new_event.event_time {
+        return Ok(Some(
+            messages.get("proposal_time_passed", &[("event", &proposal.event_name)]),
+        ));
+    }
+
+    if blocks_conflicts(&config, event_guild_id(&ctx.http, &config)) {
+        let conflicts = find_conflicts(&config, new_event.event_time, None);
+        if let Some(warning) = conflict_warning(&config, &messages, &new_event, &conflicts) {
+            return Ok(Some(format!(
+                "{}\n{}",
+                warning,
+                messages.get("conflicts_blocked", &[])
+            )));
+        }
+    }
+
+    if !claim_proposal(proposal.id) {
+        return Ok(Some(already_closed(&messages, proposal)));
+    }
+    let posted = post_proposal(ctx, proposal, &new_event);
+    release_proposal(proposal.id);
+    if !posted? {
+        return Ok(Some(already_closed(&messages, proposal)));
+    }
+
+    close_proposal(
+        ctx,
+        proposal,
+        messages.get(
+            "proposal_approved_by",
+            &[
+                ("event", &proposal.event_name),
+                ("user", &moderator.mention()),
+            ],
+        ),
+    )?;
+
+    let msg = messages.get("proposal_approved", &[("event", &proposal.event_name)]);
+    notify_proposer(ctx, proposal, &msg);
+
+    Ok(None)
+}
+
+/// Discards a proposal and lets the proposer know
+///
+/// Returns why the proposal could not be rejected, if it was already closed.
+fn reject_proposal(
+    ctx: &Context,
+    proposal: &Proposal,
+    moderator: &User,
+    reason: Option<String>,
+) -> Result<Option<String>, CommandError> {
+    let config = get_config(&ctx.data)?;
+    let messages = get_proposal_messages(ctx)?;
+
+    if !claim_proposal(proposal.id) {
+        return Ok(Some(already_closed(&messages, proposal)));
+    }
+    let removed = remove_proposal(config.db_url.clone(), proposal.id);
+    release_proposal(proposal.id);
+    if removed? == 0 {
+        return Ok(Some(already_closed(&messages, proposal)));
+    }
+
+    close_proposal(
+        ctx,
+        proposal,
+        messages.get(
+            "proposal_rejected_by",
+            &[
+                ("event", &proposal.event_name),
+                ("user", &moderator.mention()),
+            ],
+        ),
+    )?;
+
+    let mut msg = messages.get("proposal_rejected", &[("event", &proposal.event_name)]);
+    if let Some(reason) = reason {
+        msg = format!(
+            "{}\n{}",
+            msg,
+            messages.get("proposal_reason", &[("reason", &reason)])
+        );
+    }
+    notify_proposer(ctx, proposal, &msg);
+
+    Ok(None)
+}
+
+/// Posts a claimed proposal as an event and removes it
+///
+/// Returns `false` if the proposal was closed by someone else before it was claimed. It is only
+/// removed once the event is posted, so a proposal that could not be posted can be approved again.
+fn post_proposal(
+    ctx: &Context,
+    proposal: &Proposal,
+    new_event: &NewEvent,
+) -> Result<bool, CommandError> {
+    let config = get_config(&ctx.data)?;
+
+    if !proposal_exists(config.db_url.clone(), proposal.id)? {
+        return Ok(false);
+    }
+
+    publish_event(ctx, new_event, None)?;
+    remove_proposal(config.db_url.clone(), proposal.id)?;
+
+    Ok(true)
+}
+
+/// Claims a proposal for the moderator closing it, `false` if someone else is already closing it
+fn claim_proposal(proposal_id: i32) -> bool {
+    CLOSING_PROPOSALS.lock().insert(proposal_id)
+}
+
+/// Lets other moderators close a proposal again, once it has been closed or could not be
+fn release_proposal(proposal_id: i32) {
+    CLOSING_PROPOSALS.lock().remove(&proposal_id);
+}
+
+/// Tells a moderator a proposal was already approved or rejected by someone else
+fn already_closed(messages: &Messages, proposal: &Proposal) -> String {
+    messages.get("proposal_closed", &[("event", &proposal.event_name)])
+}
+
+/// Replaces the moderation message of a removed proposal with the outcome
+fn close_proposal(ctx: &Context, proposal: &Proposal, outcome: String) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+
+    if let Some(moderation_channel) = config.moderation_channel {
+        if let Ok(message_id) = proposal.message_id.parse::<u64>() {
+            ctx.http.delete_message(moderation_channel, message_id).ok();
+        }
+
+        let channel = ctx.http.get_channel(moderation_channel)?;
+        channel.id().say(&ctx.http, outcome)?;
+    }
+
+    Ok(())
+}
+
+/// Gets the messages in the language of the guild proposals are made in
+fn get_proposal_messages(ctx: &Context) -> Result<Messages, CommandError> {
+    let config = get_config(&ctx.data)?;
+
+    get_messages(&ctx.data, event_guild_id(&ctx.http, &config))
+}
+
+/// DMs the user who proposed an event
+fn notify_proposer(ctx: &Context, proposal: &Proposal, msg: &str) {
+    let config = match get_config(&ctx.data) {
+        Ok(config) => config,
+        Err(_) => return,
+    };
+
+    if let Ok(proposer_id) = proposal.proposer_id.parse::<u64>() {
+        if let Ok(user) = ctx.http.get_user(proposer_id) {
+            if wants_dm(&config, &user, Notification::Update) {
+                queue_dm(&ctx.data, ctx.http.clone(), user, msg.to_string());
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use super::time::{discord_timestamp, format_time};
+use crate::database::get_guild_settings;
+use crate::database::models::Event;
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, GuildId, Mentionable, RoleId, User};
+use std::str::FromStr;
+
+/// Most characters of mentions to put in one message, leaving room for the reminder text
+const MENTIONS_PER_MESSAGE: usize = 1800;
+
+/// How reminders for an event are sent
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReminderMode {
+    /// DM everyone interested
+    Dm,
+    /// Post once in the reminder channel, mentioning everyone interested
+    Channel,
+    /// Both DM and post in the reminder channel
+    Both,
+}
+
+impl ReminderMode {
+    /// Checks if reminders are sent by DM
+    pub fn sends_dms(self) -> bool {
+        self != ReminderMode::Channel
+    }
+
+    /// Checks if reminders are posted in the reminder channel
+    pub fn posts_in_channel(self) -> bool {
+        self != ReminderMode::Dm
+    }
+
+    /// Name of the mode, as stored and typed in commands
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReminderMode::Dm => "dm",
+            ReminderMode::Channel => "channel",
+            ReminderMode::Both => "both",
+        }
+    }
+}
+
+impl FromStr for ReminderMode {
+    /// Catalog key of the message telling the user the mode is unknown
+    type Err = &'static str;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode.to_lowercase().as_str() {
+            "dm" => Ok(ReminderMode::Dm),
+            "channel" => Ok(ReminderMode::Channel),
+            "both" => Ok(ReminderMode::Both),
+            _ => Err("reminder_mode_unknown"),
+        }
+    }
+}
+
+/// Gets how reminders are sent for an event, falling back to the guild's mode and then the config
+pub fn reminder_mode(
+    config: &HypeBotConfig,
+    event: &Event,
+    guild_id: Option<GuildId>,
+) -> ReminderMode {
+    let guild_mode = guild_id
+        .and_then(|guild_id| get_guild_settings(config.db_url.clone(), guild_id.0.to_string()).ok())
+        .and_then(|settings| settings.reminder_mode);
+
+    event
+        .reminder_mode
+        .iter()
+        .chain(guild_mode.iter())
+        .chain(std::iter::once(&config.reminder_mode))
+        .filter_map(|mode| mode.parse::<ReminderMode>().ok())
+        .next()
+        .unwrap_or(ReminderMode::Dm)
+}
+
+/// Gets the channel reminders are posted in, falling back to the config and then the event channel
+pub fn reminder_channel(config: &HypeBotConfig, guild_id: Option<GuildId>) -> ChannelId {
+    guild_id
+        .and_then(|guild_id| get_guild_settings(config.db_url.clone(), guild_id.0.to_string()).ok())
+        .and_then(|settings| settings.reminder_channel)
+        .and_then(|channel| channel.parse::<u64>().ok())
+        .or(config.reminder_channel)
+        .unwrap_or(config.event_channel)
+        .into()
+}
+
+/// Posts a reminder for an event in the reminder channel, mentioning everyone interested
+///
+/// If the event has an attendee role it is mentioned instead, otherwise mentions are split over
+/// several messages for large events.
+pub fn post_channel_reminder(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+    channel_id: ChannelId,
+    users: &[User],
+) {
+    let role_id = event
+        .role_id
+        .as_ref()
+        .and_then(|role_id| role_id.parse::<u64>().ok());
+
+    let mut chunks = vec![String::new()];
+    if let Some(role_id) = role_id {
+        chunks[0] = RoleId(role_id).mention();
+    }
+
+    for user in users.iter().filter(|_| role_id.is_none()) {
+        let mention = user.mention();
+        let chunk = chunks.last_mut().unwrap();
+
+        if chunk.len() + mention.len() >= MENTIONS_PER_MESSAGE {
+            chunks.push(format!("{} ", mention));
+        } else {
+            chunk.push_str(&mention);
+            chunk.push(' ');
+        }
+    }
+
+    let reminder = messages.get(
+        "channel_reminder",
+        &[
+            ("event", &event.event_name),
+            (
+                "time",
+                &format_time(messages, &config.event_timezone, event.event_time),
+            ),
+            ("relative", &discord_timestamp(event.event_time, 'R')),
+            ("mentions", &chunks.remove(0)),
+        ],
+    );
+
+    if let Err(e) = channel_id.say(http, reminder) {
+        error!("Unable to post reminder for {}: {}", event.event_name, e);
+        return;
+    }
+
+    for chunk in chunks {
+        channel_id.say(http, chunk).ok();
+    }
+}
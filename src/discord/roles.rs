@@ -0,0 +1,152 @@
+use super::delivery::get_reaction_users;
+use super::{event_guild_id, get_config, get_messages, interested_emoji, parse_reaction_type};
+use crate::database::models::Event;
+use crate::database::{get_event_by_msg_id, set_event_role};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use serenity::framework::standard::CommandResult;
+use serenity::http::Http;
+use serenity::model::prelude::{Reaction, RoleId, UserId};
+use serenity::prelude::Context;
+
+/// Longest role name Discord allows
+const MAX_ROLE_NAME_LEN: usize = 100;
+
+/// Creates the attendee role for an event, returning its id
+pub fn create_attendee_role(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event_name: &str,
+) -> Option<String> {
+    let guild_id = event_guild_id(http, config)?;
+    let name = attendee_role_name(messages, event_name);
+
+    match guild_id.create_role(http, |r| r.name(&name).mentionable(true)) {
+        Ok(role) => Some(role.id.0.to_string()),
+        Err(e) => {
+            error!("Unable to create attendee role for {}: {}", event_name, e);
+            None
+        }
+    }
+}
+
+/// Renames the attendee role of an event after the event has been renamed
+pub fn rename_attendee_role(
+    http: &Http,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+) {
+    let role_id = match event.role_id.as_ref().and_then(|id| id.parse::<u64>().ok()) {
+        Some(role_id) => RoleId(role_id),
+        None => return,
+    };
+
+    if let Some(guild_id) = event_guild_id(http, config) {
+        let name = attendee_role_name(messages, &event.event_name);
+        guild_id.edit_role(http, role_id, |r| r.name(&name)).ok();
+    }
+}
+
+/// Deletes the attendee role of an event
+pub fn delete_attendee_role(http: &Http, config: &HypeBotConfig, event: &Event) {
+    let role_id = match event.role_id.as_ref().and_then(|id| id.parse::<u64>().ok()) {
+        Some(role_id) => RoleId(role_id),
+        None => return,
+    };
+
+    if let Some(guild_id) = event_guild_id(http, config) {
+        if let Err(e) = guild_id.delete_role(http, role_id) {
+            error!(
+                "Unable to delete attendee role for {}: {}",
+                event.event_name, e
+            );
+        }
+    }
+}
+
+/// Gives or takes away the attendee role of an event from the user who reacted
+pub fn update_attendee_role(ctx: &Context, reaction: &Reaction, interested: bool) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+
+    // HypeBot reacts to its own announcements
+    if reaction.user_id == ctx.cache.read().user.id {
+        return Ok(());
+    }
+
+    let event = get_event_by_msg_id(config.db_url.clone(), reaction.message_id.0.to_string())?;
+    let guild_id = match reaction.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+    let role_id = match event.role_id.as_ref().and_then(|id| id.parse::<u64>().ok()) {
+        Some(role_id) => role_id,
+        // Creating the role failed when the event was posted, try again
+        None if event.attendee_role && interested => {
+            return retry_attendee_role(ctx, reaction, &config, &event);
+        }
+        None => return Ok(()),
+    };
+
+    if interested {
+        ctx.http
+            .add_member_role(guild_id.0, reaction.user_id.0, role_id)?;
+    } else {
+        ctx.http
+            .remove_member_role(guild_id.0, reaction.user_id.0, role_id)?;
+    }
+
+    Ok(())
+}
+
+/// Creates the attendee role of an event that does not have one yet, and gives it to everyone
+/// interested so far, including the user who just reacted
+fn retry_attendee_role(
+    ctx: &Context,
+    reaction: &Reaction,
+    config: &HypeBotConfig,
+    event: &Event,
+) -> CommandResult {
+    let messages = get_messages(&ctx.data, reaction.guild_id)?;
+    let role_id = match create_attendee_role(&ctx.http, config, &messages, &event.event_name) {
+        Some(role_id) => role_id,
+        None => return Ok(()),
+    };
+    set_event_role(config.db_url.clone(), event.id, Some(role_id.clone()))?;
+
+    let (guild_id, role_id) = match (reaction.guild_id, role_id.parse::<u64>()) {
+        (Some(guild_id), Ok(role_id)) => (guild_id, role_id),
+        _ => return Ok(()),
+    };
+
+    let mut users: Vec<UserId> = get_reaction_users(
+        &ctx.http,
+        reaction.channel_id,
+        reaction.message_id,
+        parse_reaction_type(&interested_emoji(config, &event.interested_emoji)),
+    )
+    .into_iter()
+    .map(|user| user.id)
+    .collect();
+    if !users.contains(&reaction.user_id) {
+        users.push(reaction.user_id);
+    }
+
+    for user_id in users {
+        ctx.http
+            .add_member_role(guild_id.0, user_id.0, role_id)
+            .ok();
+    }
+
+    Ok(())
+}
+
+/// Gets the name of the attendee role for an event, shortened to fit Discord's limit
+fn attendee_role_name(messages: &Messages, event_name: &str) -> String {
+    messages
+        .get("attendee_role", &[("event", event_name)])
+        .chars()
+        .take(MAX_ROLE_NAME_LEN)
+        .collect()
+}
@@ -0,0 +1,174 @@
+use super::reminders::ReminderMode;
+use super::{get_config, get_messages};
+use crate::database::{set_guild_conflicts, set_guild_language, set_guild_reminders};
+use crate::messages::MessageCatalog;
+use serenity::framework::standard::{macros::command, Args, CommandError, CommandResult};
+use serenity::model::prelude::Message;
+use serenity::prelude::Context;
+use serenity::utils::parse_channel;
+
+#[command]
+/// Sets the language the bot uses in this server
+///
+/// `~language es`
+///
+/// **Languages**
+/// Run `~language` without a language to list the available languages
+fn language(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let languages = {
+        let data = ctx.data.read();
+        let catalog = data
+            .get::<MessageCatalog>()
+            .ok_or(CommandError("Unable to get message catalog".to_string()))?;
+
+        catalog.languages()
+    };
+
+    let language = match args.single::<String>() {
+        Ok(language) => language.to_lowercase(),
+        Err(_) => {
+            msg.reply(
+                &ctx,
+                messages.get(
+                    "languages_available",
+                    &[("languages", &languages.join(", "))],
+                ),
+            )?;
+            return Ok(());
+        }
+    };
+
+    if !languages.contains(&language) {
+        msg.reply(
+            &ctx,
+            messages.get("language_unknown", &[("languages", &languages.join(", "))]),
+        )?;
+        return Ok(());
+    }
+
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    set_guild_language(
+        config.db_url.clone(),
+        guild_id.0.to_string(),
+        language.clone(),
+    )?;
+
+    // Confirm in the new language
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    msg.reply(
+        &ctx,
+        messages.get("language_set", &[("language", &language)]),
+    )?;
+
+    Ok(())
+}
+
+#[command]
+/// Sets how event reminders are sent in this server
+///
+/// `~reminders both #reminders`
+///
+/// **Modes**
+/// `dm`: DM everyone interested in the event
+/// `channel`: post one reminder in the reminder channel, mentioning everyone interested
+/// `both`: do both
+///
+/// **Channel**
+/// Optionally mention the channel to post reminders in, defaults to the configured reminder channel
+/// or the event channel. Events can override the mode when they are created.
+fn reminders(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let mode = match args
+        .single::<String>()
+        .map(|mode| mode.parse::<ReminderMode>())
+    {
+        Ok(Ok(mode)) => mode,
+        Ok(Err(e)) => {
+            msg.reply(&ctx, messages.get(e, &[]))?;
+            return Ok(());
+        }
+        Err(_) => {
+            msg.reply(&ctx, messages.get("reminder_mode_missing", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let channel = match args.single::<String>() {
+        Ok(channel) => match parse_channel(&channel) {
+            Some(channel) => Some(channel),
+            None => {
+                msg.reply(&ctx, messages.get("invalid_channel", &[]))?;
+                return Ok(());
+            }
+        },
+        Err(_) => None,
+    };
+
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    set_guild_reminders(
+        config.db_url.clone(),
+        guild_id.0.to_string(),
+        mode.as_str().to_string(),
+        channel.map(|channel| channel.to_string()),
+    )?;
+
+    msg.reply(
+        &ctx,
+        messages.get("reminder_mode_set", &[("mode", mode.as_str())]),
+    )?;
+
+    Ok(())
+}
+
+#[command]
+/// Sets what happens when an event overlaps another event in this server
+///
+/// `~conflicts block`
+///
+/// **Modes**
+/// `warn`: show a warning when the event is previewed or edited
+/// `block`: refuse to create, post, or move events that overlap another event
+///
+/// **Note**
+/// Events are assumed to last an hour. Attendees who are interested in overlapping events are
+/// always warned.
+fn conflicts(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let block = match args.single::<String>().map(|mode| mode.to_lowercase()) {
+        Ok(ref mode) if mode == "warn" => false,
+        Ok(ref mode) if mode == "block" => true,
+        _ => {
+            msg.reply(&ctx, messages.get("conflict_mode_unknown", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    set_guild_conflicts(config.db_url.clone(), guild_id.0.to_string(), block)?;
+
+    if block {
+        msg.reply(&ctx, messages.get("conflict_mode_block", &[]))?;
+    } else {
+        msg.reply(&ctx, messages.get("conflict_mode_warn", &[]))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,268 @@
+use super::delivery::{queue_delivery, queue_dm, DELIVERY_INTERVAL};
+use super::permissions::{has_permission, Permission};
+use super::{
+    event_guild_id, get_config, get_messages, parse_reaction_type, wants_dm, Notification,
+    NUMBER_EMOJIS,
+};
+use crate::database::models::{Event, NewFeedback, NewSurvey};
+use crate::database::{
+    get_attended_count, get_co_organizers, get_feedback_by_msg_id, get_survey_by_event_name,
+    get_survey_by_id, get_survey_feedback, get_uncommented_feedback, insert_feedback,
+    insert_survey, set_feedback_comment, set_feedback_rating,
+};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use chrono::{Duration, Utc};
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::http::Http;
+use serenity::model::prelude::{Message, Reaction, ReactionType};
+use serenity::prelude::{Context, RwLock, ShareMap};
+use std::sync::Arc;
+use std::thread;
+
+/// Highest rating an event can be given, ratings are reacted with the numbers from 1 up to this
+const MAX_RATING: usize = 5;
+/// Days after a survey is sent that replies are still taken as comments
+const COMMENT_DAYS: i64 = 7;
+/// Longest summary that fits in a Discord message
+const MAX_SUMMARY_LEN: usize = 2000;
+
+#[command]
+/// Shows the feedback attendees gave an event
+///
+/// `~feedback "event name"`
+///
+/// **Note**
+/// Only the organizers of the event or an admin can see its feedback.
+fn feedback(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let event_name = args.single_quoted::<String>()?.replace("\"", "");
+    let survey = match get_survey_by_event_name(config.db_url.clone(), event_name) {
+        Ok(survey) => survey,
+        Err(_) => {
+            msg.reply(&ctx, messages.get("survey_not_found", &[]))?;
+            return Ok(());
+        }
+    };
+
+    let guild_id = match msg.guild_id {
+        Some(guild_id) => guild_id,
+        None => return Ok(()),
+    };
+
+    let author_id = msg.author.id.0.to_string();
+    let is_organizer = survey.creator_id == author_id
+        || survey
+            .co_organizers
+            .iter()
+            .flat_map(|co_organizers| co_organizers.split(','))
+            .any(|user_id| user_id == author_id);
+
+    if !is_organizer && !has_permission(ctx, guild_id, &msg.author, Permission::Admin) {
+        msg.reply(
+            &ctx,
+            messages.get("feedback_denied", &[("event", &survey.event_name)]),
+        )?;
+        return Ok(());
+    }
+
+    let responses = get_survey_feedback(config.db_url.clone(), survey.id)?;
+    let ratings: Vec<i32> = responses.iter().filter_map(|f| f.rating).collect();
+    let average = if ratings.is_empty() {
+        messages.get("average_rating_none", &[])
+    } else {
+        format!(
+            "{:.1}/5",
+            ratings.iter().sum::<i32>() as f64 / ratings.len() as f64
+        )
+    };
+
+    let attended = get_attended_count(config.db_url.clone(), survey.event_id).unwrap_or_default();
+
+    let mut summary = messages.get(
+        "feedback_summary",
+        &[
+            ("event", &survey.event_name),
+            ("attended", &attended.to_string()),
+            ("sent", &responses.len().to_string()),
+            ("ratings", &ratings.len().to_string()),
+            ("average", &average),
+        ],
+    );
+
+    for comment in responses.iter().filter_map(|f| f.comment.as_ref()) {
+        let line = format!("\n> {}", comment.replace("\n", " "));
+        if summary.len() + line.len() > MAX_SUMMARY_LEN {
+            break;
+        }
+        summary.push_str(&line);
+    }
+
+    msg.channel_id.say(&ctx.http, summary)?;
+
+    Ok(())
+}
+
+/// Queues a survey DM to everyone who attended an event
+pub fn send_surveys(
+    data: &Arc<RwLock<ShareMap>>,
+    http: Arc<Http>,
+    config: HypeBotConfig,
+    messages: Messages,
+    event: Event,
+    attendees: Vec<String>,
+) {
+    // The event is removed once it is over, so its co-organizers are kept with the survey
+    let co_organizers = get_co_organizers(config.db_url.clone(), event.id).unwrap_or_default();
+
+    queue_delivery(data, move || {
+        let survey = match insert_survey(
+            config.db_url.clone(),
+            &NewSurvey {
+                event_id: event.id,
+                event_name: event.event_name.clone(),
+                creator_id: event.creator_id.clone(),
+                sent_time: Utc::now().naive_utc(),
+                co_organizers: Some(co_organizers.join(",")).filter(|ids| !ids.is_empty()),
+            },
+        ) {
+            Ok(survey) => survey,
+            Err(e) => {
+                error!("Unable to create survey for {}: {}", event.event_name, e);
+                return;
+            }
+        };
+
+        let request = messages.get("survey_request", &[("event", &event.event_name)]);
+
+        for user_id in attendees {
+            let user = match user_id.parse::<u64>().map(|user_id| http.get_user(user_id)) {
+                Ok(Ok(user)) => user,
+                _ => continue,
+            };
+
+            if !wants_dm(&config, &user, Notification::Survey) {
+                continue;
+            }
+
+            let survey_msg = match user
+                .create_dm_channel(&http)
+                .and_then(|dm_channel| dm_channel.send_message(&http, |m| m.content(&request)))
+            {
+                Ok(survey_msg) => survey_msg,
+                Err(_) => continue,
+            };
+
+            for emoji in NUMBER_EMOJIS[..MAX_RATING].iter() {
+                survey_msg.react(&http, parse_reaction_type(emoji)).ok();
+            }
+
+            insert_feedback(
+                config.db_url.clone(),
+                &NewFeedback {
+                    survey_id: survey.id,
+                    user_id,
+                    message_id: survey_msg.id.0.to_string(),
+                },
+            )
+            .ok();
+
+            thread::sleep(DELIVERY_INTERVAL);
+        }
+    });
+}
+
+/// Records the rating an attendee reacted with on a survey DM
+pub fn survey_reaction(ctx: &Context, reaction: &Reaction) -> CommandResult {
+    if reaction.guild_id.is_some() || reaction.user_id == ctx.cache.read().user.id {
+        return Ok(());
+    }
+
+    let rating = match &reaction.emoji {
+        ReactionType::Unicode(emoji) => {
+            match NUMBER_EMOJIS[..MAX_RATING].iter().position(|e| e == emoji) {
+                Some(index) => index as i32 + 1,
+                None => return Ok(()),
+            }
+        }
+        _ => return Ok(()),
+    };
+
+    let config = get_config(&ctx.data)?;
+    let feedback =
+        match get_feedback_by_msg_id(config.db_url.clone(), reaction.message_id.0.to_string()) {
+            Ok(feedback) => feedback,
+            Err(_) => return Ok(()),
+        };
+
+    if feedback.user_id == reaction.user_id.0.to_string() {
+        set_feedback_rating(config.db_url.clone(), feedback.id, rating)?;
+    }
+
+    Ok(())
+}
+
+/// Records a DM reply to a survey as the attendee's comments
+///
+/// A reply to a specific survey is recorded on that survey. Any other message only counts if a
+/// single survey is still taking comments, otherwise the attendee is asked to reply to one.
+pub fn survey_comment(ctx: &Context, msg: &Message) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+
+    if msg.guild_id.is_some() || msg.author.bot || msg.content.starts_with(&config.prefix) {
+        return Ok(());
+    }
+
+    let oldest_sent = Utc::now().naive_utc() - Duration::days(COMMENT_DAYS);
+    let pending: Vec<_> =
+        get_uncommented_feedback(config.db_url.clone(), msg.author.id.0.to_string())?
+            .into_iter()
+            .filter_map(|feedback| {
+                get_survey_by_id(config.db_url.clone(), feedback.survey_id)
+                    .ok()
+                    .filter(|survey| survey.sent_time >= oldest_sent)
+                    .map(|survey| (feedback, survey))
+            })
+            .collect();
+
+    let replied_to = msg
+        .message_reference
+        .as_ref()
+        .and_then(|reference| reference.message_id)
+        .map(|message_id| message_id.0.to_string());
+
+    let messages = get_messages(&ctx.data, event_guild_id(&ctx.http, &config))?;
+    let (feedback, survey) = match (replied_to, pending.as_slice()) {
+        (Some(replied_to), _) => match pending
+            .iter()
+            .find(|(feedback, _)| feedback.message_id == replied_to)
+        {
+            Some(pending) => pending,
+            None => return Ok(()),
+        },
+        (None, [pending]) => pending,
+        (None, []) => return Ok(()),
+        (None, _) => {
+            queue_dm(
+                &ctx.data,
+                ctx.http.clone(),
+                msg.author.clone(),
+                messages.get("survey_choose", &[]),
+            );
+            return Ok(());
+        }
+    };
+
+    set_feedback_comment(config.db_url.clone(), feedback.id, msg.content.clone())?;
+
+    queue_dm(
+        &ctx.data,
+        ctx.http.clone(),
+        msg.author.clone(),
+        messages.get("survey_thanks", &[("event", &survey.event_name)]),
+    );
+
+    Ok(())
+}
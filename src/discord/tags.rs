@@ -0,0 +1,370 @@
+use super::delivery::{get_reaction_users, queue_delivery, DELIVERY_INTERVAL};
+use super::discussion::message_link;
+use super::options::{parse_flag, take_options};
+use super::time::format_time;
+use super::{
+    event_guild_id, get_config, get_messages, parse_reaction_type, send_dm_message,
+    uninterested_emoji, user_timezone, wants_dm, Notification,
+};
+use crate::database::models::{Event, NewSubscription};
+use crate::database::{
+    add_subscription, get_tag_subscriptions, get_user_subscriptions, remove_subscription,
+};
+use crate::hypebot_config::HypeBotConfig;
+use crate::messages::Messages;
+use serenity::framework::standard::{macros::command, Args, CommandResult};
+use serenity::http::Http;
+use serenity::model::prelude::{ChannelId, Message, MessageId, User};
+use serenity::prelude::{Context, RwLock, ShareMap};
+use serenity::utils::Colour;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::thread;
+
+#[command]
+/// Subscribes you to a tag, so you are DMed when an event with it is posted
+///
+/// `~subscribe gaming` or `~subscribe gaming rsvp:yes`
+///
+/// **Auto-RSVP**
+/// With `rsvp:yes` you also get reminders for every event with the tag without reacting to it,
+/// react as not interested to skip one
+///
+/// Run `~subscribe` on its own to see your subscriptions and the available tags.
+fn subscribe(ctx: &mut Context, msg: &Message, args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+    let (mut args, options) = take_options(&args, &["rsvp"]);
+    let user_id = msg.author.id.0.to_string();
+
+    let tag = match args.single::<String>() {
+        Ok(tag) => tag.to_lowercase(),
+        Err(_) => {
+            let subscriptions: Vec<String> =
+                get_user_subscriptions(config.db_url.clone(), user_id)?
+                    .into_iter()
+                    .map(|subscription| {
+                        if subscription.auto_rsvp {
+                            messages.get("subscription_auto_rsvp", &[("tag", &subscription.tag)])
+                        } else {
+                            format!("`{}`", subscription.tag)
+                        }
+                    })
+                    .collect();
+
+            let mut reply = if subscriptions.is_empty() {
+                messages.get("subscriptions_none", &[])
+            } else {
+                messages.get("subscriptions_list", &[("tags", &subscriptions.join(", "))])
+            };
+            if !config.tags.is_empty() {
+                reply.push('\n');
+                reply.push_str(&messages.get("tags_available", &[("tags", &known_tags(&config))]));
+            }
+
+            msg.reply(&ctx, reply)?;
+            return Ok(());
+        }
+    };
+
+    if let Some(unknown) = unknown_tags(&config, &messages, std::slice::from_ref(&tag)) {
+        msg.reply(&ctx, unknown)?;
+        return Ok(());
+    }
+
+    let auto_rsvp = match options.get("rsvp").map(|rsvp| parse_flag(rsvp)) {
+        Some(Some(auto_rsvp)) => auto_rsvp,
+        Some(None) => {
+            msg.reply(&ctx, messages.get("invalid_rsvp_option", &[]))?;
+            return Ok(());
+        }
+        None => false,
+    };
+
+    add_subscription(
+        config.db_url.clone(),
+        &NewSubscription {
+            user_id,
+            tag: tag.clone(),
+            auto_rsvp,
+        },
+    )?;
+
+    if auto_rsvp {
+        msg.reply(&ctx, messages.get("subscribed_auto_rsvp", &[("tag", &tag)]))?;
+    } else {
+        msg.reply(&ctx, messages.get("subscribed", &[("tag", &tag)]))?;
+    }
+
+    Ok(())
+}
+
+#[command]
+/// Unsubscribes you from a tag
+///
+/// `~unsubscribe gaming`
+fn unsubscribe(ctx: &mut Context, msg: &Message, mut args: Args) -> CommandResult {
+    let config = get_config(&ctx.data)?;
+    let messages = get_messages(&ctx.data, msg.guild_id)?;
+
+    let tag = match args.single::<String>() {
+        Ok(tag) => tag.to_lowercase(),
+        Err(_) => {
+            msg.reply(&ctx, messages.get("no_tag", &[]))?;
+            return Ok(());
+        }
+    };
+
+    if remove_subscription(
+        config.db_url.clone(),
+        msg.author.id.0.to_string(),
+        tag.clone(),
+    )? > 0
+    {
+        msg.reply(&ctx, messages.get("unsubscribed", &[("tag", &tag)]))?;
+    } else {
+        msg.reply(&ctx, messages.get("not_subscribed", &[("tag", &tag)]))?;
+    }
+
+    Ok(())
+}
+
+/// Parses the `tags` option, a comma separated list like `gaming,social`
+pub fn parse_tags(value: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+
+    for tag in value.split(',') {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+
+    tags
+}
+
+/// Splits the stored tags of an event
+pub fn event_tags(tags: &Option<String>) -> Vec<String> {
+    tags.as_deref().map(parse_tags).unwrap_or_default()
+}
+
+/// Describes the tags that are not configured, `None` if they all are
+///
+/// Any tag is allowed if no tags are configured.
+pub fn unknown_tags(
+    config: &HypeBotConfig,
+    messages: &Messages,
+    tags: &[String],
+) -> Option<String> {
+    if config.tags.is_empty() {
+        return None;
+    }
+
+    let unknown: Vec<&String> = tags
+        .iter()
+        .filter(|tag| !config.tags.contains_key(*tag))
+        .collect();
+
+    if unknown.is_empty() {
+        None
+    } else {
+        Some(messages.get(
+            "tag_unknown",
+            &[("tag", unknown[0]), ("tags", &known_tags(config))],
+        ))
+    }
+}
+
+/// Lists the configured tags
+fn known_tags(config: &HypeBotConfig) -> String {
+    let mut tags: Vec<String> = config.tags.keys().map(|tag| format!("`{}`", tag)).collect();
+    tags.sort();
+    tags.join(", ")
+}
+
+/// Gets the embed color of the first tag that has one configured
+pub fn tag_color(config: &HypeBotConfig, tags: &Option<String>) -> Option<Colour> {
+    event_tags(tags).iter().find_map(|tag| {
+        config
+            .tags
+            .get(tag)
+            .and_then(|tag_config| tag_config.color.as_ref())
+            .and_then(|color| u32::from_str_radix(color.trim_start_matches('#'), 16).ok())
+            .map(Colour::new)
+    })
+}
+
+/// Gets the thumbnail of the first tag that has one configured
+pub fn tag_thumbnail(config: &HypeBotConfig, tags: &[String]) -> Option<String> {
+    tags.iter().find_map(|tag| {
+        config
+            .tags
+            .get(tag)
+            .and_then(|tag_config| tag_config.thumbnail.clone())
+    })
+}
+
+/// Queues a DM to everyone subscribed to a tag of a newly posted event
+pub fn notify_subscribers(
+    data: &Arc<RwLock<ShareMap>>,
+    http: &Arc<Http>,
+    config: &HypeBotConfig,
+    messages: &Messages,
+    event: &Event,
+) {
+    let tags = event_tags(&event.tags);
+    if tags.is_empty() {
+        return;
+    }
+
+    let subscriptions = match get_tag_subscriptions(config.db_url.clone(), &tags) {
+        Ok(subscriptions) => subscriptions,
+        Err(e) => {
+            error!("Unable to get subscribers of {}: {}", event.event_name, e);
+            return;
+        }
+    };
+
+    // One DM per user, mentioning reminders if any of their subscriptions auto-RSVPs
+    let mut subscribers: BTreeMap<String, bool> = BTreeMap::new();
+    for subscription in subscriptions {
+        *subscribers.entry(subscription.user_id).or_insert(false) |= subscription.auto_rsvp;
+    }
+    subscribers.remove(&event.creator_id);
+
+    let link = match (
+        event_guild_id(http, config),
+        event.message_id.parse::<u64>(),
+    ) {
+        (Some(guild_id), Ok(message_id)) => message_link(
+            guild_id,
+            ChannelId(config.event_channel),
+            MessageId(message_id),
+        ),
+        _ => String::new(),
+    };
+
+    let http = http.clone();
+    let config = config.clone();
+    let messages = messages.clone();
+    let event = event.clone();
+
+    queue_delivery(data, move || {
+        for (user_id, auto_rsvp) in subscribers {
+            let user = match user_id.parse::<u64>().map(|user_id| http.get_user(user_id)) {
+                Ok(Ok(user)) => user,
+                _ => continue,
+            };
+
+            if !wants_dm(&config, &user, Notification::Update) {
+                continue;
+            }
+
+            let msg_key = if auto_rsvp {
+                "subscription_event_rsvp"
+            } else {
+                "subscription_event"
+            };
+            let tz = user_timezone(&config, &user);
+            let msg = messages.get(
+                msg_key,
+                &[
+                    ("event", &event.event_name),
+                    ("tags", &tags.join(", ")),
+                    ("time", &format_time(&messages, &tz, event.event_time)),
+                    ("link", &link),
+                    (
+                        "emoji",
+                        &uninterested_emoji(&config, &event.uninterested_emoji),
+                    ),
+                ],
+            );
+
+            send_dm_message(&http, user, &msg);
+            thread::sleep(DELIVERY_INTERVAL);
+        }
+    });
+}
+
+/// Gets the users auto-RSVP'd to an event through their subscriptions
+///
+/// Users who reacted as not interested in the event are left out.
+fn auto_rsvp_users(http: &Http, config: &HypeBotConfig, event: &Event) -> Vec<User> {
+    let tags = event_tags(&event.tags);
+    let message_id = match event.message_id.parse::<u64>() {
+        Ok(message_id) if !tags.is_empty() => MessageId(message_id),
+        _ => return Vec::new(),
+    };
+
+    let mut user_ids: Vec<String> = get_tag_subscriptions(config.db_url.clone(), &tags)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|subscription| subscription.auto_rsvp)
+        .map(|subscription| subscription.user_id)
+        .collect();
+    user_ids.sort();
+    user_ids.dedup();
+
+    if user_ids.is_empty() {
+        return Vec::new();
+    }
+
+    let uninterested = get_reaction_users(
+        http,
+        ChannelId(config.event_channel),
+        message_id,
+        parse_reaction_type(&uninterested_emoji(config, &event.uninterested_emoji)),
+    );
+
+    user_ids
+        .into_iter()
+        .filter_map(|user_id| user_id.parse::<u64>().ok())
+        .filter(|user_id| !uninterested.iter().any(|user| user.id.0 == *user_id))
+        .filter_map(|user_id| http.get_user(user_id).ok())
+        .collect()
+}
+
+/// Adds the users auto-RSVP'd to an event to the users who reacted as interested
+pub fn with_auto_rsvp_users(
+    http: &Http,
+    config: &HypeBotConfig,
+    event: &Event,
+    mut users: Vec<User>,
+) -> Vec<User> {
+    for user in auto_rsvp_users(http, config, event) {
+        if !users.iter().any(|interested| interested.id == user.id) {
+            users.push(user);
+        }
+    }
+
+    users
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tags_trims_and_lowercases() {
+        assert_eq!(parse_tags(" Gaming , social"), vec!["gaming", "social"]);
+    }
+
+    #[test]
+    fn parse_tags_skips_empty_and_repeated_tags() {
+        assert_eq!(
+            parse_tags("gaming,,GAMING, ,study"),
+            vec!["gaming", "study"]
+        );
+        assert!(parse_tags("").is_empty());
+        assert!(parse_tags(" , ").is_empty());
+    }
+
+    #[test]
+    fn event_tags_splits_stored_tags() {
+        assert_eq!(
+            event_tags(&Some("gaming,study".to_string())),
+            vec!["gaming", "study"]
+        );
+        assert!(event_tags(&None).is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::messages::Messages;
+use chrono::offset::TimeZone;
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+
+/// Parses a time in the configured timezone into UTC
+///
+/// Accepts either an absolute time (`04:20pm 2069-04-20`), a day and time relative to now
+/// (`monday 9am`, `next saturday 7:30pm`, `tomorrow 18:00`, `8pm`), or a delay (`in 90m`).
+pub fn parse_event_time(tz: &Tz, date_string: &str) -> Option<NaiveDateTime> {
+    parse_event_time_from(tz, Utc::now(), date_string)
+}
+
+/// Parses a time in the configured timezone into UTC, with relative times counted from `now`
+pub fn parse_event_time_from(
+    tz: &Tz,
+    now: DateTime<Utc>,
+    date_string: &str,
+) -> Option<NaiveDateTime> {
+    let date_string = date_string.trim();
+
+    let local_time = match NaiveDateTime::parse_from_str(date_string, "%I:%M%P %Y-%m-%d") {
+        Ok(local_time) => local_time,
+        Err(_) => parse_relative_time(now.with_timezone(tz).naive_local(), date_string)?,
+    };
+
+    let local_time = tz.from_local_datetime(&local_time).earliest()?;
+
+    Some(local_time.with_timezone(&Utc).naive_utc())
+}
+
+/// Parses a day and time, or a delay, relative to the current local time
+fn parse_relative_time(now: NaiveDateTime, date_string: &str) -> Option<NaiveDateTime> {
+    let date_string = date_string.to_lowercase();
+
+    if let Some(delay) = date_string.strip_prefix("in ") {
+        return parse_delay(delay).map(|delay| now + delay);
+    }
+
+    let mut next = false;
+    let mut day = None;
+    let mut time = None;
+
+    for token in date_string.split_whitespace() {
+        if token == "next" {
+            next = true;
+        } else if token == "at" {
+            continue;
+        } else if let Some(offset) = parse_day(token, now.weekday()) {
+            day = Some(offset);
+        } else if let Some(parsed_time) = parse_time(token) {
+            time = Some(parsed_time);
+        } else {
+            return None;
+        }
+    }
+
+    let time = time?;
+    let days = match day {
+        // A weekday that is today means next week if the time has passed or "next" was given
+        Some((0, true)) if next || time <= now.time() => 7,
+        Some((days, _)) => days,
+        // No day given, use the next time the clock hits this time
+        None if now.time() < time => 0,
+        None => 1,
+    };
+
+    Some((now.date() + Duration::days(days)).and_time(time))
+}
+
+/// Parses a delay like `90m`, `2h`, `1h30m` or `45 minutes`
+fn parse_delay(delay: &str) -> Option<Duration> {
+    let delay: String = delay.split_whitespace().collect();
+    let mut total = Duration::zero();
+    let mut rest = delay.as_str();
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount = rest[..digits].parse::<i64>().ok()?;
+        rest = &rest[digits..];
+
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        total = total
+            + match &rest[..unit] {
+                "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(amount),
+                "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(amount),
+                "d" | "day" | "days" => Duration::days(amount),
+                _ => return None,
+            };
+        rest = &rest[unit..];
+    }
+
+    Some(total).filter(|total| *total > Duration::zero())
+}
+
+/// Parses a day name into the number of days from today, and if the day was a weekday
+fn parse_day(token: &str, today: Weekday) -> Option<(i64, bool)> {
+    let weekday = match token {
+        "today" => return Some((0, false)),
+        "tomorrow" => return Some((1, false)),
+        "monday" | "mon" => Weekday::Mon,
+        "tuesday" | "tue" | "tues" => Weekday::Tue,
+        "wednesday" | "wed" => Weekday::Wed,
+        "thursday" | "thu" | "thurs" => Weekday::Thu,
+        "friday" | "fri" => Weekday::Fri,
+        "saturday" | "sat" => Weekday::Sat,
+        "sunday" | "sun" => Weekday::Sun,
+        _ => return None,
+    };
+
+    let days =
+        (7 + weekday.num_days_from_monday() as i64 - today.num_days_from_monday() as i64) % 7;
+
+    Some((days, true))
+}
+
+/// Parses a time of day like `9am`, `7:30pm`, or `18:00`
+fn parse_time(token: &str) -> Option<NaiveTime> {
+    let (clock, offset) = if let Some(clock) = token.strip_suffix("am") {
+        (clock, Some(0))
+    } else if let Some(clock) = token.strip_suffix("pm") {
+        (clock, Some(12))
+    } else {
+        (token, None)
+    };
+
+    let mut parts = clock.splitn(2, ':');
+    let hour = parts.next()?.parse::<u32>().ok()?;
+    let minute = match parts.next() {
+        Some(minute) => minute.parse::<u32>().ok()?,
+        // A bare number is only a time with am or pm
+        None if offset.is_some() => 0,
+        None => return None,
+    };
+
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+/// Formats a UTC time in a timezone using the catalog's time format
+pub fn format_time(messages: &Messages, tz: &Tz, time: NaiveDateTime) -> String {
+    DateTime::<Utc>::from_utc(time, Utc)
+        .with_timezone(tz)
+        .format(&messages.get("time_format", &[]))
+        .to_string()
+}
+
+/// Formats a UTC time as Discord timestamp markup, shown in each viewer's own timezone
+///
+/// Style is one of Discord's timestamp styles, e.g. `F` for the full date or `R` for relative.
+pub fn discord_timestamp(time: NaiveDateTime, style: char) -> String {
+    format!("<t:{}:{}>", time.timestamp(), style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    /// A Wednesday afternoon
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd(2020, 11, 4).and_hms(15, 0, 0)
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> Option<NaiveDateTime> {
+        Some(NaiveDate::from_ymd(2020, 11, day).and_hms(hour, minute, 0))
+    }
+
+    #[test]
+    fn parse_time_handles_midnight_and_noon() {
+        assert_eq!(parse_time("12am"), NaiveTime::from_hms_opt(0, 0, 0));
+        assert_eq!(parse_time("12pm"), NaiveTime::from_hms_opt(12, 0, 0));
+        assert_eq!(parse_time("12:30am"), NaiveTime::from_hms_opt(0, 30, 0));
+        assert_eq!(parse_time("7:30pm"), NaiveTime::from_hms_opt(19, 30, 0));
+        assert_eq!(parse_time("18:00"), NaiveTime::from_hms_opt(18, 0, 0));
+    }
+
+    #[test]
+    fn parse_time_rejects_invalid_times() {
+        assert_eq!(parse_time("13pm"), None);
+        assert_eq!(parse_time("0am"), None);
+        assert_eq!(parse_time("9"), None);
+        assert_eq!(parse_time("25:00"), None);
+        assert_eq!(parse_time("noon"), None);
+    }
+
+    #[test]
+    fn parse_day_counts_days_until_the_weekday() {
+        assert_eq!(parse_day("today", Weekday::Wed), Some((0, false)));
+        assert_eq!(parse_day("tomorrow", Weekday::Wed), Some((1, false)));
+        assert_eq!(parse_day("wed", Weekday::Wed), Some((0, true)));
+        assert_eq!(parse_day("friday", Weekday::Wed), Some((2, true)));
+        assert_eq!(parse_day("monday", Weekday::Wed), Some((5, true)));
+        assert_eq!(parse_day("someday", Weekday::Wed), None);
+    }
+
+    #[test]
+    fn relative_time_without_day_uses_next_occurrence() {
+        assert_eq!(parse_relative_time(now(), "8pm"), at(4, 20, 0));
+        assert_eq!(parse_relative_time(now(), "9am"), at(5, 9, 0));
+        assert_eq!(parse_relative_time(now(), "3pm"), at(5, 15, 0));
+    }
+
+    #[test]
+    fn relative_time_rolls_weekday_over() {
+        assert_eq!(parse_relative_time(now(), "wednesday 8pm"), at(4, 20, 0));
+        assert_eq!(parse_relative_time(now(), "wednesday 9am"), at(11, 9, 0));
+        assert_eq!(
+            parse_relative_time(now(), "next wednesday 8pm"),
+            at(11, 20, 0)
+        );
+        assert_eq!(parse_relative_time(now(), "monday at 9am"), at(9, 9, 0));
+        assert_eq!(parse_relative_time(now(), "tomorrow 18:00"), at(5, 18, 0));
+    }
+
+    #[test]
+    fn relative_time_handles_delays() {
+        assert_eq!(parse_relative_time(now(), "in 90m"), at(4, 16, 30));
+        assert_eq!(parse_relative_time(now(), "in 2h"), at(4, 17, 0));
+        assert_eq!(parse_relative_time(now(), "in 1h30m"), at(4, 16, 30));
+        assert_eq!(parse_relative_time(now(), "in 45 minutes"), at(4, 15, 45));
+        assert_eq!(parse_relative_time(now(), "in 0m"), None);
+        assert_eq!(parse_relative_time(now(), "in soon"), None);
+    }
+
+    #[test]
+    fn relative_time_rejects_unknown_words() {
+        assert_eq!(parse_relative_time(now(), "friday evening"), None);
+        assert_eq!(parse_relative_time(now(), "friday"), None);
+    }
+}
@@ -1,8 +1,10 @@
+use crate::messages::DEFAULT_LANGUAGE;
 use chrono_tz::Tz;
 use config::{Config, ConfigError, File};
 use serde::de::{self, Error, Visitor};
 use serde::{Deserialize, Deserializer};
 use serenity::prelude::TypeMapKey;
+use std::collections::HashMap;
 use std::fmt;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -13,9 +15,86 @@ pub struct HypeBotConfig {
     pub prefix: String,
     pub event_channel: u64,
     pub event_roles: Vec<u64>,
+    #[serde(default)]
+    pub edit_roles: Vec<u64>,
+    #[serde(default)]
+    pub cancel_roles: Vec<u64>,
+    #[serde(default)]
+    pub admin_roles: Vec<u64>,
+    pub moderation_channel: Option<u64>,
+    pub dm_fallback_channel: Option<u64>,
     #[serde(deserialize_with = "from_tz_string")]
     pub event_timezone: Tz,
     pub log_path: String,
+    #[serde(default = "default_language")]
+    pub language: String,
+    pub message_catalog: Option<String>,
+    #[serde(default = "default_reminder_mode")]
+    pub reminder_mode: String,
+    pub reminder_channel: Option<u64>,
+    pub event_category: Option<u64>,
+    pub discussion_channel: Option<u64>,
+    pub archive_category: Option<u64>,
+    pub digest_channel: Option<u64>,
+    #[serde(default = "default_digest_schedule")]
+    pub digest_schedule: String,
+    #[serde(default)]
+    pub digest_pin: bool,
+    #[serde(default)]
+    pub calendar: bool,
+    #[serde(default)]
+    pub tags: HashMap<String, TagConfig>,
+    #[serde(default = "default_interested_emoji")]
+    pub interested_emoji: String,
+    #[serde(default = "default_uninterested_emoji")]
+    pub uninterested_emoji: String,
+    #[serde(default = "default_approve_emoji")]
+    pub approve_emoji: String,
+    #[serde(default = "default_reject_emoji")]
+    pub reject_emoji: String,
+    #[serde(default = "default_checkin_emoji")]
+    pub checkin_emoji: String,
+}
+
+/// How events with a tag are shown
+#[derive(Debug, Deserialize, Clone)]
+pub struct TagConfig {
+    /// Embed color as a hex code, like "#1abc9c"
+    pub color: Option<String>,
+    /// Thumbnail link used when the event does not have one
+    pub thumbnail: Option<String>,
+}
+
+fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+fn default_reminder_mode() -> String {
+    "dm".to_string()
+}
+
+fn default_digest_schedule() -> String {
+    "monday 9am".to_string()
+}
+
+fn default_interested_emoji() -> String {
+    "\u{2705}".to_string()
+}
+
+fn default_uninterested_emoji() -> String {
+    "\u{274C}".to_string()
+}
+
+fn default_approve_emoji() -> String {
+    "\u{1F44D}".to_string()
+}
+
+fn default_reject_emoji() -> String {
+    "\u{1F44E}".to_string()
+}
+
+fn default_checkin_emoji() -> String {
+    "\u{1F4CD}".to_string()
 }
 
 struct ConfigValueVisitor;
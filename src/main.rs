@@ -5,6 +5,8 @@ extern crate diesel_migrations;
 extern crate serde;
 #[macro_use]
 extern crate log;
+#[macro_use]
+extern crate lazy_static;
 extern crate log4rs;
 
 use chrono::{DateTime, Utc};
@@ -27,7 +29,7 @@ use serenity::model::channel::{Message, Reaction};
 use serenity::model::id::UserId;
 use serenity::model::prelude::Ready;
 use serenity::prelude::{Context, EventHandler, RwLock};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::process::exit;
 use std::sync::Arc;
 use white_rabbit::{DateResult, Scheduler};
@@ -36,18 +38,37 @@ use std::path::Path;
 mod database;
 mod discord;
 mod hypebot_config;
+mod messages;
 
-use database::models::NewEvent;
 use database::*;
-use discord::events::{CANCEL_COMMAND, CONFIRM_COMMAND, CREATE_COMMAND};
+use discord::attendance::{check_in_reaction, CHECKIN_COMMAND, STATS_COMMAND};
+use discord::calendar::update_calendar;
+use discord::conflicts::warn_rsvp_conflicts;
+use discord::delivery::start_delivery_queue;
+use discord::digest::schedule_digest;
+use discord::events::{
+    CANCEL_COMMAND, CLONE_EVENT_COMMAND, CONFIRM_COMMAND, CREATE_COMMAND, EDIT_COMMAND,
+    PENDING_COMMAND, PUBLISH_COMMAND,
+};
+use discord::organizers::{
+    notify_organizers, ADDORGANIZER_COMMAND, REMOVEORGANIZER_COMMAND, TRANSFER_COMMAND,
+    UNREACHABLE_COMMAND,
+};
+use discord::permissions::permission_check;
+use discord::polls::{schedule_poll, POLL_COMMAND};
+use discord::preferences::{NOTIFICATIONS_COMMAND, TIMEZONE_COMMAND};
+use discord::proposals::{moderation_reaction, APPROVE_COMMAND, PROPOSE_COMMAND, REJECT_COMMAND};
+use discord::roles::update_attendee_role;
+use discord::settings::{CONFLICTS_COMMAND, LANGUAGE_COMMAND, REMINDERS_COMMAND};
+use discord::surveys::{survey_comment, survey_reaction, FEEDBACK_COMMAND};
+use discord::tags::{SUBSCRIBE_COMMAND, UNSUBSCRIBE_COMMAND};
 use discord::{
-    delete_event, get_config, get_scheduler, log_error, permission_check,
-    send_message_to_reaction_users, schedule_event, DraftEvent, SchedulerKey,
+    delete_event, get_config, get_scheduler, is_interested_reaction, log_error, schedule_event,
+    schedule_publish, send_message_to_reaction_users, DraftEvent, SchedulerKey,
+    EVENT_DURATION_MINUTES,
 };
 use hypebot_config::HypeBotConfig;
-
-const INTERESTED_EMOJI: &str = "\u{2705}";
-const UNINTERESTED_EMOJI: &str = "\u{274C}";
+use messages::MessageCatalog;
 
 type HypeBotResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -55,9 +76,44 @@ type HypeBotResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 #[group]
 #[only_in(guilds)]
 #[description("Commands for Creating Events")]
-#[commands(create, confirm, cancel)]
+#[commands(create, confirm, pending, publish, edit, cancel, poll, clone_event)]
 struct EventCommands;
 
+/// Attendance command group
+#[group]
+#[only_in(guilds)]
+#[description("Commands for Attendance")]
+#[commands(checkin, stats, feedback)]
+struct AttendanceCommands;
+
+/// Preference command group
+#[group]
+#[only_in(guilds)]
+#[description("Commands for Personal Preferences")]
+#[commands(timezone, notifications, subscribe, unsubscribe)]
+struct PreferenceCommands;
+
+/// Settings command group
+#[group]
+#[only_in(guilds)]
+#[description("Commands for Configuring HypeBot")]
+#[commands(language, reminders, conflicts)]
+struct SettingsCommands;
+
+/// Organizer command group
+#[group]
+#[only_in(guilds)]
+#[description("Commands for Managing Event Organizers")]
+#[commands(addorganizer, removeorganizer, transfer, unreachable)]
+struct OrganizerCommands;
+
+/// Proposal command group
+#[group]
+#[only_in(guilds)]
+#[description("Commands for Proposing Events")]
+#[commands(propose, approve, reject)]
+struct ProposalCommands;
+
 /// Handler for Discord events
 struct Handler;
 
@@ -71,12 +127,29 @@ impl EventHandler for Handler {
                 return;
             }
         };
-        if reaction.channel_id.0 == config.event_channel && reaction.emoji.as_data() == INTERESTED_EMOJI {
-            send_message_to_reaction_users(
-                &ctx,
-                &reaction,
-                "Hello, you are now receiving reminders for **{event}**",
-            );
+        if is_interested_reaction(&config, &reaction) {
+            send_message_to_reaction_users(&ctx, &reaction, "reminders_on");
+            notify_organizers(&ctx, &reaction, true);
+
+            if let Err(e) = update_attendee_role(&ctx, &reaction, true) {
+                error!("Unable to give attendee role: {}", e.0);
+            }
+
+            if let Err(e) = warn_rsvp_conflicts(&ctx, &reaction) {
+                error!("Unable to warn about conflicting RSVPs: {}", e.0);
+            }
+        }
+
+        if let Err(e) = check_in_reaction(&ctx, &reaction) {
+            error!("Unable to check in: {}", e.0);
+        }
+
+        if let Err(e) = survey_reaction(&ctx, &reaction) {
+            error!("Unable to record survey rating: {}", e.0);
+        }
+
+        if let Err(e) = moderation_reaction(&ctx, &reaction) {
+            error!("Unable to handle moderation reaction: {}", e.0);
         }
     }
 
@@ -89,12 +162,20 @@ impl EventHandler for Handler {
                 return;
             }
         };
-        if reaction.channel_id.0 == config.event_channel && reaction.emoji.as_data() == INTERESTED_EMOJI {
-            send_message_to_reaction_users(
-                &ctx,
-                &reaction,
-                "Hello, you are no longer receiving reminders for **{event}**",
-            );
+        if is_interested_reaction(&config, &reaction) {
+            send_message_to_reaction_users(&ctx, &reaction, "reminders_off");
+            notify_organizers(&ctx, &reaction, false);
+
+            if let Err(e) = update_attendee_role(&ctx, &reaction, false) {
+                error!("Unable to remove attendee role: {}", e.0);
+            }
+        }
+    }
+
+    /// On message
+    fn message(&self, ctx: Context, msg: Message) {
+        if let Err(e) = survey_comment(&ctx, &msg) {
+            error!("Unable to record survey comment: {}", e.0);
         }
     }
 
@@ -207,6 +288,15 @@ fn main() -> HypeBotResult<()> {
         // Setup logging
         setup_logging(&cfg)?;
 
+        // Load message catalog
+        let catalog = match MessageCatalog::new(cfg.message_catalog.as_deref()) {
+            Ok(catalog) => catalog,
+            Err(err) => {
+                error!("Error opening message catalog: {}", err);
+                exit(-1);
+            }
+        };
+
         // Run migrations
         let connection = establish_connection(cfg.db_url.clone());
         embedded_migrations::run(&connection)?;
@@ -227,6 +317,11 @@ fn main() -> HypeBotResult<()> {
                 .before(permission_check)
                 .after(log_error)
                 .group(&EVENTCOMMANDS_GROUP)
+                .group(&ORGANIZERCOMMANDS_GROUP)
+                .group(&PROPOSALCOMMANDS_GROUP)
+                .group(&ATTENDANCECOMMANDS_GROUP)
+                .group(&PREFERENCECOMMANDS_GROUP)
+                .group(&SETTINGSCOMMANDS_GROUP)
                 .help(&BOT_HELP),
         );
 
@@ -234,19 +329,8 @@ fn main() -> HypeBotResult<()> {
         {
             let mut data = client.data.write();
             data.insert::<HypeBotConfig>(cfg);
-            data.insert::<DraftEvent>(DraftEvent {
-                event: NewEvent {
-                    message_id: String::new(),
-                    event_time: Utc::now().naive_utc(),
-                    event_name: String::new(),
-                    organizer: String::new(),
-                    event_desc: String::new(),
-                    event_loc: String::new(),
-                    thumbnail_link: String::new(),
-                    reminder_sent: 0 as i32,
-                },
-                creator_id: 0,
-            });
+            data.insert::<MessageCatalog>(catalog);
+            data.insert::<DraftEvent>(HashMap::new());
 
             // Create scheduler
             let scheduler = Scheduler::new(2);
@@ -254,14 +338,20 @@ fn main() -> HypeBotResult<()> {
             data.insert::<SchedulerKey>(scheduler);
         }
 
+        // Start sending DMs
+        start_delivery_queue(&client.data);
+
         // Schedule current events
         let config = get_config(&client.data).expect("Unable to find get config");
-        let duration = chrono::Duration::minutes(60);
+        let duration = chrono::Duration::minutes(EVENT_DURATION_MINUTES);
         for event in get_all_events(config.db_url.clone()).unwrap() {
             let event_time: DateTime<Utc> =
                 DateTime::<Utc>::from_utc(event.event_time.clone(), Utc);
 
-            if Utc::now() > event_time + duration {
+            if event.publish_time.is_some() {
+                // Pending events that already started are dropped when publishing
+                schedule_publish(&client.cache_and_http.http, &client.data, &event);
+            } else if Utc::now() > event_time + duration {
                 delete_event(&client.cache_and_http.http, &client.data, &event);
             } else if Utc::now() > event_time {
                 let scheduler = get_scheduler(&client.data).unwrap();
@@ -279,6 +369,17 @@ fn main() -> HypeBotResult<()> {
             }
         }
 
+        // Catch the calendar up with events cleaned up while the bot was offline
+        update_calendar(&client.cache_and_http.http, &client.data);
+
+        // Schedule open polls
+        for poll in get_all_polls(config.db_url.clone()).unwrap() {
+            schedule_poll(&client.cache_and_http.http, &client.data, &poll);
+        }
+
+        // Schedule the weekly digest
+        schedule_digest(&client.cache_and_http.http, &client.data);
+
         // Start bot
         info!("Starting HypeBot!");
         if let Err(why) = client.start() {
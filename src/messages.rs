@@ -0,0 +1,84 @@
+use config::{Config, ConfigError, File, FileFormat};
+use serenity::prelude::TypeMapKey;
+use std::collections::HashMap;
+use strfmt::strfmt;
+
+/// Messages bundled with the bot, used for anything missing from the configured catalog
+const BUNDLED_MESSAGES: &str = include_str!("../messages.toml");
+
+/// Language used when a message is missing from the selected language
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// All user facing messages, by language
+#[derive(Debug, Clone)]
+pub struct MessageCatalog {
+    languages: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageCatalog {
+    /// Loads the bundled catalog, overridden by the catalog file at `catalog_path` if one is given
+    pub fn new(catalog_path: Option<&str>) -> Result<Self, ConfigError> {
+        let mut cfg = Config::new();
+        cfg.merge(File::from_str(BUNDLED_MESSAGES, FileFormat::Toml))?;
+
+        if let Some(catalog_path) = catalog_path {
+            cfg.merge(File::with_name(catalog_path))?;
+        }
+
+        Ok(MessageCatalog {
+            languages: cfg.try_into()?,
+        })
+    }
+
+    /// Lists the languages in the catalog
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.languages.keys().cloned().collect();
+        languages.sort();
+        languages
+    }
+
+    /// Gets the messages for a language, falling back to English for missing messages
+    pub fn messages(&self, language: &str) -> Messages {
+        let mut messages = self
+            .languages
+            .get(DEFAULT_LANGUAGE)
+            .cloned()
+            .unwrap_or_default();
+
+        if let Some(translated) = self.languages.get(language) {
+            messages.extend(translated.clone());
+        }
+
+        Messages { messages }
+    }
+}
+
+impl TypeMapKey for MessageCatalog {
+    type Value = MessageCatalog;
+}
+
+/// User facing messages in a single language
+#[derive(Debug, Clone)]
+pub struct Messages {
+    messages: HashMap<String, String>,
+}
+
+impl Messages {
+    /// Gets a message, filling in its `{placeholder}`s
+    pub fn get(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let template = match self.messages.get(key) {
+            Some(template) => template,
+            None => {
+                warn!("Message '{}' is missing from the message catalog", key);
+                return key.to_string();
+            }
+        };
+
+        let args: HashMap<String, String> = args
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        strfmt(template, &args).unwrap_or_else(|_| template.clone())
+    }
+}